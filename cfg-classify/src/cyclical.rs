@@ -1,17 +1,19 @@
 //! Cycle detection and elimination.
 
 use std::collections::BTreeMap;
+use std::slice;
 
 use bit_matrix::BitMatrix;
 use bit_vec::BitVec;
 
 use cfg_grammar::{
     rule::{cfg_rule::CfgRule, RuleRef},
+    symbol::set::SymbolBitSet,
     AsRuleRef, RuleContainer,
 };
 use cfg_symbol::Symbol;
 
-use crate::derivation;
+use cfg_grammar::derivation;
 
 /// Provides information about cycles among unit derivations in the grammar. There are two ways of
 /// pruning cycles.
@@ -62,6 +64,38 @@ where
         }
     }
 
+    /// Checks whether the grammar has cycles once nullable RHS symbols are removed from rules.
+    /// For example, `A ::= A B` together with `B ::= ()` is such a cycle. These cycles
+    /// include all unit cycles.
+    pub fn nullable_cycles(&self) -> bool {
+        let unit_derivation = derivation::nullable_unit_derivation_matrix(&*self.grammar);
+        (0..self.grammar.num_syms()).any(|i| unit_derivation[(i, i)])
+    }
+
+    /// Returns rules that participate in a cycle once their nullable RHS symbols are removed.
+    pub fn nullable_cycle_participants(&'a self) -> Vec<RuleRef<'a>> {
+        let unit_derivation = derivation::nullable_unit_derivation_matrix(&*self.grammar);
+        let nullable = SymbolBitSet::nullable_set(&*self.grammar);
+        self.grammar
+            .rules()
+            .filter(|rule| {
+                let non_nullable: Vec<_> = rule
+                    .rhs
+                    .iter()
+                    .filter(|&&sym| !nullable.has_sym(sym))
+                    .collect();
+                let candidates = match &non_nullable[..] {
+                    [] => rule.rhs,
+                    &[sym] => slice::from_ref(sym),
+                    _ => &[],
+                };
+                candidates
+                    .iter()
+                    .any(|&sym| unit_derivation[(sym.into(), rule.lhs.into())])
+            })
+            .collect()
+    }
+
     /// Removes all rules that participate in a cycle. Doesn't preserve the language represented
     /// by the grammar.
     pub fn remove_cycles(&mut self) {
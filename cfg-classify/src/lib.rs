@@ -1,10 +1,11 @@
 //! Classification of rules and grammars.
 
 // mod linear;
-// mod recursive;
 pub mod cyclical;
-mod derivation;
 #[cfg(feature = "cfg-predict")]
 pub mod ll;
 pub mod lr;
+pub mod recursive;
+pub mod regular;
+pub mod stats;
 pub mod useful;
@@ -5,7 +5,7 @@ use std::collections::BTreeMap;
 use cfg_grammar::rhs_closure::RhsClosure;
 use cfg_grammar::symbol::set::SymbolBitSet;
 use cfg_grammar::RuleContainer;
-use cfg_predict::{FirstSets, FollowSets, PredictSets};
+use cfg_predict::{FirstSets, FollowSets, PredictSets, PredictTerminal};
 use cfg_symbol::Symbol;
 
 /// LL parse table.
@@ -49,11 +49,10 @@ where
         let follow = FollowSets::new(grammar, start_sym, first.predict_sets());
         // LlParseTable[A,a] contains the rule A → w if and only if
         // a is in FIRST(w) or
-        // None is in FIRST(w) and a is in FOLLOW(A).
-        for (rule_idx, rule) in grammar.rules().enumerate() {
+        // ε is in FIRST(w) and a is in FOLLOW(A).
+        for (rule_idx, rule) in grammar.indexed_rules() {
             let rhs_first_set = first.first_set_for_string(rule.rhs);
-            let identity = |maybe_terminal| maybe_terminal;
-            for &terminal in rhs_first_set.iter().flat_map(identity) {
+            for terminal in rhs_first_set.iter().filter_map(|t| t.terminal()) {
                 let key = LlParseTableKey {
                     nonterminal: rule.lhs,
                     terminal,
@@ -61,9 +60,9 @@ where
                 let entry = this.map.entry(key).or_insert(vec![]);
                 entry.push(rule_idx);
             }
-            if rhs_first_set.contains(&None) {
+            if rhs_first_set.contains(&PredictTerminal::Epsilon) {
                 let lhs_follow_set = follow.predict_sets().get(&rule.lhs).unwrap();
-                for &terminal in lhs_follow_set.iter().flat_map(identity) {
+                for terminal in lhs_follow_set.iter().filter_map(|t| t.terminal()) {
                     let key = LlParseTableKey {
                         nonterminal: rule.lhs,
                         terminal,
@@ -49,6 +49,54 @@ pub struct Lr0Node {
     pub link: BTreeMap<Symbol, SetId>,
 }
 
+/// Size metrics of an LR(0) Finite State Machine.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FsmStats {
+    /// The number of states.
+    pub states: usize,
+    /// The number of transitions through terminals.
+    pub shift_edges: usize,
+    /// The number of items with the dot at the end, summed over all states.
+    pub reduce_items: usize,
+    /// The number of states that have a shift-reduce or reduce-reduce conflict.
+    pub conflicts: usize,
+}
+
+/// Computes size metrics of an LR(0) Finite State Machine.
+pub fn fsm_stats(nodes: &[Lr0Node]) -> FsmStats {
+    let mut stats = FsmStats {
+        states: nodes.len(),
+        ..FsmStats::default()
+    };
+    for node in nodes {
+        let reduce_items = node
+            .items
+            .map
+            .values()
+            .filter(|item| item.dot as usize == item.rhs.len())
+            .count();
+        stats.shift_edges += node.link.len();
+        stats.reduce_items += reduce_items;
+        if reduce_items > 1 || (reduce_items == 1 && !node.link.is_empty()) {
+            stats.conflicts += 1;
+        }
+    }
+    stats
+}
+
+/// Returns `(state_id, rule_id)` for every LR(0) item with the dot at the end of its RHS,
+/// in order of states. Rule ids refer to the grammar the FSM was built from, including the
+/// augmented start rule.
+pub fn reduce_items(nodes: &[Lr0Node]) -> impl Iterator<Item = (SetId, RuleId)> + '_ {
+    nodes.iter().enumerate().flat_map(|(state_id, node)| {
+        node.items
+            .map
+            .iter()
+            .filter(|(_, item)| item.dot as usize == item.rhs.len())
+            .map(move |(&rule_id, _)| (state_id as SetId, rule_id))
+    })
+}
+
 impl Lr0Items {
     fn new() -> Self {
         Lr0Items {
@@ -77,7 +125,7 @@ where
 
         while let Some(item) = self.queue.pop_front() {
             if let Some(nonterminal_postdot) = self.nonterminal_postdot(&item) {
-                for (rule_idx, rule) in self.grammar.rules().enumerate() {
+                for (rule_idx, rule) in self.grammar.indexed_rules() {
                     if rule.lhs == nonterminal_postdot {
                         let new_item = Lr0Item {
                             rhs: rule.rhs.to_vec(),
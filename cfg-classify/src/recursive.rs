@@ -1,47 +1,144 @@
+//! Classification of recursive rules.
+
+use std::collections::BTreeSet;
+
 use bit_matrix::BitMatrix;
+use bit_vec::BitVec;
 
+use cfg_grammar::derivation;
+use cfg_grammar::symbol::set::SymbolBitSet;
 use cfg_grammar::{rule::RuleRef, RuleContainer};
-
-use crate::derivation;
+use cfg_symbol::Symbol;
 
 /// Calculation of parts of grammar that participate in recursion,
 /// be it left-recursion, right-recursion or middle-recursion.
 pub struct Recursion<'a, G> {
     grammar: &'a G,
     derivation: BitMatrix,
-    distances: Vec<Vec<Option<u32>>>,
-    prediction_distances: Vec<Option<u32>>,
-    completion_distances: Vec<Option<u32>>,
-    min_of: Vec<Option<u32>>,
+    nullable: BitVec,
 }
 
-struct RecursiveRules<'a, G, R>
-    where G: 'a
-{
-    rules: R,
-    recursion: &'a Recursion<'a, G>,
+/// The kind of recursion that occurs in a rule.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RecursionKind {
+    /// Only the first RHS symbol derives the LHS, or a symbol preceded only by
+    /// nullable symbols does, which is hidden left recursion.
+    Left,
+    /// Only the last RHS symbol derives the LHS.
+    Right,
+    /// Neither the first nor the last RHS symbol derives the LHS, but some other one does.
+    Middle,
+    /// The rule is left-recursive as in `Left`, and the last RHS symbol derives the LHS.
+    All,
+}
+
+/// A recursive rule, together with the kind of its recursion.
+#[derive(Clone, Copy)]
+pub struct RecursiveRule<'a> {
+    /// Reference to a rule.
+    pub rule: RuleRef<'a>,
+    /// The kind of recursion.
+    pub recursion: RecursionKind,
+}
+
+/// Counts of recursive rules for each kind of recursion.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RecursionSummary {
+    /// The number of left-recursive rules.
+    pub left: usize,
+    /// The number of right-recursive rules.
+    pub right: usize,
+    /// The number of middle-recursive rules.
+    pub middle: usize,
+    /// The number of rules that are both left- and right-recursive.
+    pub all: usize,
 }
 
 impl<'a, G> Recursion<'a, G>
-    where G: RuleContainer
+where
+    G: RuleContainer,
 {
-    /// Returns a new `MinimalDistance` for a grammar.
+    /// Analyzes recursion in the grammar's rules.
     pub fn new(grammar: &'a G) -> Self {
-        let reachability = derivation::reachability_matrix(grammar);
-        let distances = grammar.rules().map(|rule| vec![None; rule.rhs().len() + 1]).collect();
+        let nullable = SymbolBitSet::nullable_set(grammar).into_bit_vec();
         Recursion {
-            grammar: grammar,
-            // reachability,
-            prediction_distances: vec![None; grammar.num_syms()],
-            completion_distances: vec![None; grammar.num_syms()],
-            min_of: vec![None; grammar.num_syms()],
+            grammar,
+            derivation: derivation::reachability_matrix(grammar),
+            nullable,
         }
     }
 
-    fn rules<'b>(&'b self) -> RecursiveRules<'b, G, impl Iterator<Item = RuleRef<'a>>> {
-        RecursiveRules {
-            rules: self.grammar.rules(),
-            recursion: self,
+    /// Returns an iterator over the grammar's recursive rules.
+    pub fn recursive_rules(&self) -> impl Iterator<Item = RecursiveRule<'a>> + '_ {
+        self.grammar.rules().filter_map(move |rule| {
+            self.recursion_kind(rule)
+                .map(|recursion| RecursiveRule { rule, recursion })
+        })
+    }
+
+    /// Counts recursive rules of each kind.
+    pub fn summary(&self) -> RecursionSummary {
+        let mut summary = RecursionSummary::default();
+        for recursive_rule in self.recursive_rules() {
+            match recursive_rule.recursion {
+                RecursionKind::Left => summary.left += 1,
+                RecursionKind::Right => summary.right += 1,
+                RecursionKind::Middle => summary.middle += 1,
+                RecursionKind::All => summary.all += 1,
+            }
+        }
+        summary
+    }
+
+    /// Returns the length of the longest chain of right-recursive rules, or `None` if no rule
+    /// is right-recursive. A chain consists of nonterminals with right-recursive rules that
+    /// derive one another, such as `A ::= x B` and `B ::= y A`, which form a chain of 2.
+    pub fn max_right_recursion_chain(&self) -> Option<usize> {
+        let lhss: BTreeSet<Symbol> = self
+            .recursive_rules()
+            .filter(|recursive| {
+                matches!(
+                    recursive.recursion,
+                    RecursionKind::Right | RecursionKind::All
+                )
+            })
+            .map(|recursive| recursive.rule.lhs)
+            .collect();
+        lhss.iter()
+            .map(|a| {
+                lhss.iter()
+                    .filter(|b| {
+                        self.derivation[(a.usize(), b.usize())]
+                            && self.derivation[(b.usize(), a.usize())]
+                    })
+                    .count()
+            })
+            .max()
+    }
+
+    /// Returns the kind of recursion in a rule, or `None` if the rule is not recursive.
+    ///
+    /// Left recursion is detected at any RHS position that is preceded only by
+    /// nullable symbols.
+    pub fn recursion_kind(&self, rule: RuleRef) -> Option<RecursionKind> {
+        let derives_lhs = |sym: &Symbol| self.derivation[(sym.usize(), rule.lhs.usize())];
+        let mut left = false;
+        for sym in rule.rhs {
+            if derives_lhs(sym) {
+                left = true;
+                break;
+            }
+            if !self.nullable[sym.usize()] {
+                break;
+            }
+        }
+        let right = rule.rhs.last().is_some_and(derives_lhs);
+        match (left, right) {
+            (true, true) => Some(RecursionKind::All),
+            (true, false) => Some(RecursionKind::Left),
+            (false, true) => Some(RecursionKind::Right),
+            (false, false) if rule.rhs.iter().any(derives_lhs) => Some(RecursionKind::Middle),
+            (false, false) => None,
         }
     }
 }
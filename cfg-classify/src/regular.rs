@@ -0,0 +1,364 @@
+//! Regular grammars and their conversion to finite automata.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::iter;
+
+use cfg_grammar::rule::RuleRef;
+use cfg_grammar::symbol::set::SymbolBitSet;
+use cfg_grammar::RuleContainer;
+use cfg_symbol::{Symbol, SymbolSource};
+
+type StateId = u32;
+
+/// A deterministic finite automaton over terminal symbols. The start state has the ID 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dfa {
+    states: Vec<DfaState>,
+}
+
+/// A state of a deterministic finite automaton.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DfaState {
+    /// List of transitions through terminals.
+    pub link: BTreeMap<Symbol, StateId>,
+    /// Indicates whether input that ends in this state is accepted.
+    pub accepting: bool,
+}
+
+/// The error returned when a grammar is not right-linear.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotRegularError {
+    /// The left-hand side of the first rule that is not right-linear.
+    pub lhs: Symbol,
+    /// The right-hand side of that rule.
+    pub rhs: Vec<Symbol>,
+}
+
+/// A regular expression built during state elimination.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Regex {
+    Empty,
+    Epsilon,
+    Sym(Symbol),
+    Concat(Vec<Regex>),
+    Alt(Vec<Regex>),
+    Star(Box<Regex>),
+}
+
+/// A nondeterministic finite automaton with epsilon transitions. States with IDs lower than
+/// the number of the grammar's symbols correspond to nonterminals.
+struct Nfa {
+    link: Vec<Vec<(Option<Symbol>, usize)>>,
+    accepting: usize,
+}
+
+impl Dfa {
+    /// Converts a right-linear grammar into a DFA. Every rule must have the form `A ::= a b c B`
+    /// or `A ::= a b c`, where `B` is a nonterminal and `a b c` is a string of zero or
+    /// more terminals.
+    ///
+    /// Nonterminals are treated as states of a nondeterministic automaton, which is then
+    /// determinized with the subset construction.
+    pub fn new<G>(grammar: &G, start_sym: Symbol) -> Result<Self, NotRegularError>
+    where
+        G: RuleContainer,
+    {
+        let nfa = Nfa::new(grammar, grammar.rules())?;
+        Ok(nfa.determinize(start_sym.usize()))
+    }
+
+    /// Returns the ID of the start state.
+    pub fn start(&self) -> StateId {
+        0
+    }
+
+    /// Returns all states, indexed by their IDs.
+    pub fn states(&self) -> &[DfaState] {
+        &self.states[..]
+    }
+
+    /// Returns the state reached from `state` through the given terminal, if any.
+    pub fn transition(&self, state: StateId, terminal: Symbol) -> Option<StateId> {
+        self.states[state as usize].link.get(&terminal).cloned()
+    }
+
+    /// Checks whether the automaton accepts the given string of terminals.
+    pub fn accepts(&self, input: &[Symbol]) -> bool {
+        let mut state = self.start();
+        for &terminal in input {
+            match self.transition(state, terminal) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        self.states[state as usize].accepting
+    }
+}
+
+impl Nfa {
+    fn new<'a, G>(
+        grammar: &G,
+        rules: impl Iterator<Item = RuleRef<'a>>,
+    ) -> Result<Self, NotRegularError>
+    where
+        G: RuleContainer,
+    {
+        let terminal_set = SymbolBitSet::terminal_set(grammar);
+        let num_syms = grammar.num_syms();
+        let mut nfa = Nfa {
+            link: vec![vec![]; num_syms + 1],
+            accepting: num_syms,
+        };
+        for rule in rules {
+            // Split the RHS into a string of terminals and an optional trailing nonterminal.
+            let (terminals, nonterminal) = match rule.rhs.split_last() {
+                Some((&last, init)) if !terminal_set.has_sym(last) => (init, Some(last)),
+                _ => (rule.rhs, None),
+            };
+            if terminals.iter().any(|&sym| !terminal_set.has_sym(sym)) {
+                return Err(NotRegularError {
+                    lhs: rule.lhs,
+                    rhs: rule.rhs.to_vec(),
+                });
+            }
+            let target = nonterminal.map_or(nfa.accepting, |sym| sym.usize());
+            let mut state = rule.lhs.usize();
+            for (i, &terminal) in terminals.iter().enumerate() {
+                let next = if i + 1 == terminals.len() {
+                    target
+                } else {
+                    nfa.link.push(vec![]);
+                    nfa.link.len() - 1
+                };
+                nfa.link[state].push((Some(terminal), next));
+                state = next;
+            }
+            if terminals.is_empty() {
+                nfa.link[state].push((None, target));
+            }
+        }
+        Ok(nfa)
+    }
+
+    fn epsilon_closure(&self, states: impl IntoIterator<Item = usize>) -> BTreeSet<usize> {
+        let mut closure = BTreeSet::new();
+        let mut work_stack: Vec<usize> = states.into_iter().collect();
+        while let Some(state) = work_stack.pop() {
+            if closure.insert(state) {
+                for &(terminal, next) in &self.link[state] {
+                    if terminal.is_none() {
+                        work_stack.push(next);
+                    }
+                }
+            }
+        }
+        closure
+    }
+
+    fn determinize(&self, start: usize) -> Dfa {
+        let mut states = vec![];
+        let mut ids = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        let initial = self.epsilon_closure(iter::once(start));
+        ids.insert(initial.clone(), 0);
+        queue.push_back(initial);
+        // Sets are numbered in the order they are queued, so states are pushed in order of IDs.
+        while let Some(set) = queue.pop_front() {
+            let mut targets: BTreeMap<Symbol, BTreeSet<usize>> = BTreeMap::new();
+            for &state in &set {
+                for &(terminal, next) in &self.link[state] {
+                    if let Some(terminal) = terminal {
+                        targets.entry(terminal).or_default().insert(next);
+                    }
+                }
+            }
+            let mut link = BTreeMap::new();
+            for (terminal, next_states) in targets {
+                let closure = self.epsilon_closure(next_states);
+                let id = match ids.get(&closure) {
+                    Some(&id) => id,
+                    None => {
+                        let id = ids.len() as StateId;
+                        ids.insert(closure.clone(), id);
+                        queue.push_back(closure);
+                        id
+                    }
+                };
+                link.insert(terminal, id);
+            }
+            states.push(DfaState {
+                link,
+                accepting: set.contains(&self.accepting),
+            });
+        }
+        Dfa { states }
+    }
+}
+
+/// Converts the sub-grammar reachable from `sym` into a regular expression, or returns `None`
+/// if that sub-grammar is not right-linear. Rules that are not reachable from `sym` are
+/// ignored.
+///
+/// Symbols are written by name, or as `g<id>` when unnamed, and separated by spaces.
+/// Alternatives are separated by `|`, the empty string is written as `()`, and `∅` stands for
+/// the empty language. The expression is obtained through state elimination on the grammar's
+/// DFA.
+pub fn nonterminal_to_regex<G>(grammar: &G, sym: Symbol) -> Option<String>
+where
+    G: RuleContainer,
+{
+    let mut reachable = BTreeSet::new();
+    let mut work_stack = vec![sym];
+    while let Some(lhs) = work_stack.pop() {
+        if reachable.insert(lhs) {
+            for rule in grammar.rules().filter(|rule| rule.lhs == lhs) {
+                work_stack.extend(rule.rhs.iter().cloned());
+            }
+        }
+    }
+    let rules = grammar.rules().filter(|rule| reachable.contains(&rule.lhs));
+    let dfa = Nfa::new(grammar, rules).ok()?.determinize(sym.usize());
+    let mut regex = dfa.to_regex().render(grammar.sym_source());
+    if regex.is_empty() {
+        regex.push_str("()");
+    }
+    Some(regex)
+}
+
+impl Dfa {
+    /// Eliminates states one by one, from the highest ID down to the start state.
+    fn to_regex(&self) -> Regex {
+        let n = self.states.len();
+        let (start, end) = (n, n + 1);
+        let mut edges: BTreeMap<(usize, usize), Regex> = BTreeMap::new();
+        edges.insert((start, 0), Regex::Epsilon);
+        for (id, state) in self.states.iter().enumerate() {
+            if state.accepting {
+                edges.insert((id, end), Regex::Epsilon);
+            }
+            for (&terminal, &next) in &state.link {
+                let edge = edges.remove(&(id, next as usize)).unwrap_or(Regex::Empty);
+                edges.insert((id, next as usize), edge.alt(Regex::Sym(terminal)));
+            }
+        }
+        for state in (0..n).rev() {
+            let looped = edges.remove(&(state, state)).unwrap_or(Regex::Empty).star();
+            let incoming: Vec<_> = edges
+                .iter()
+                .filter(|&(&(_, to), _)| to == state)
+                .map(|(&(from, _), regex)| (from, regex.clone()))
+                .collect();
+            let outgoing: Vec<_> = edges
+                .iter()
+                .filter(|&(&(from, _), _)| from == state)
+                .map(|(&(_, to), regex)| (to, regex.clone()))
+                .collect();
+            edges.retain(|&(from, to), _| from != state && to != state);
+            for (from, before) in &incoming {
+                for (to, after) in &outgoing {
+                    let path = before.clone().concat(looped.clone()).concat(after.clone());
+                    let edge = edges.remove(&(*from, *to)).unwrap_or(Regex::Empty);
+                    edges.insert((*from, *to), edge.alt(path));
+                }
+            }
+        }
+        edges.remove(&(start, end)).unwrap_or(Regex::Empty)
+    }
+}
+
+impl Regex {
+    fn concat(self, other: Regex) -> Regex {
+        match (self, other) {
+            (Regex::Empty, _) | (_, Regex::Empty) => Regex::Empty,
+            (Regex::Epsilon, regex) | (regex, Regex::Epsilon) => regex,
+            (Regex::Concat(mut left), Regex::Concat(right)) => {
+                left.extend(right);
+                Regex::Concat(left)
+            }
+            (Regex::Concat(mut left), right) => {
+                left.push(right);
+                Regex::Concat(left)
+            }
+            (left, Regex::Concat(mut right)) => {
+                right.insert(0, left);
+                Regex::Concat(right)
+            }
+            (left, right) => Regex::Concat(vec![left, right]),
+        }
+    }
+
+    fn alt(self, other: Regex) -> Regex {
+        let mut alternatives = vec![];
+        for regex in [self, other] {
+            match regex {
+                Regex::Empty => {}
+                Regex::Alt(inner) => alternatives.extend(inner),
+                regex => alternatives.push(regex),
+            }
+        }
+        let mut unique = vec![];
+        for regex in alternatives {
+            if !unique.contains(&regex) {
+                unique.push(regex);
+            }
+        }
+        match unique.len() {
+            0 => Regex::Empty,
+            1 => unique.pop().unwrap(),
+            _ => Regex::Alt(unique),
+        }
+    }
+
+    fn star(self) -> Regex {
+        match self {
+            Regex::Empty | Regex::Epsilon => Regex::Epsilon,
+            Regex::Star(inner) => Regex::Star(inner),
+            regex => Regex::Star(Box::new(regex)),
+        }
+    }
+
+    /// Writes the expression. The empty string is written as nothing.
+    fn render(&self, sym_source: &SymbolSource) -> String {
+        match self {
+            Regex::Empty => "∅".to_string(),
+            Regex::Epsilon => String::new(),
+            &Regex::Sym(sym) => match sym_source.name_of(sym) {
+                Some(name) => name.to_string(),
+                None => format!("g{}", sym.usize()),
+            },
+            Regex::Concat(parts) => parts
+                .iter()
+                .map(|part| part.render_grouped(sym_source))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Regex::Alt(alternatives) if alternatives.contains(&Regex::Epsilon) => {
+                let rest: Vec<_> = alternatives
+                    .iter()
+                    .filter(|&regex| *regex != Regex::Epsilon)
+                    .cloned()
+                    .collect();
+                match &rest[..] {
+                    [regex] => format!("{}?", regex.render_grouped(sym_source)),
+                    _ => format!("({})?", Regex::Alt(rest).render(sym_source)),
+                }
+            }
+            Regex::Alt(alternatives) => alternatives
+                .iter()
+                .map(|regex| regex.render(sym_source))
+                .collect::<Vec<_>>()
+                .join(" | "),
+            Regex::Star(inner) => format!("{}*", inner.render_grouped(sym_source)),
+        }
+    }
+
+    /// Writes the expression, in parentheses if it consists of several parts.
+    fn render_grouped(&self, sym_source: &SymbolSource) -> String {
+        match self {
+            Regex::Alt(alternatives) if alternatives.contains(&Regex::Epsilon) => {
+                self.render(sym_source)
+            }
+            Regex::Concat(_) | Regex::Alt(_) => format!("({})", self.render(sym_source)),
+            _ => self.render(sym_source),
+        }
+    }
+}
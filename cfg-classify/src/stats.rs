@@ -0,0 +1,67 @@
+//! Summary metrics of grammars.
+
+use cfg_grammar::derivation;
+use cfg_grammar::symbol::set::SymbolBitSet;
+use cfg_grammar::RuleContainer;
+
+use crate::recursive::{Recursion, RecursionSummary};
+
+/// Metrics of a grammar, computed in one go.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GrammarStats {
+    /// The number of symbols in use.
+    pub symbols: usize,
+    /// The number of rules.
+    pub rules: usize,
+    /// The number of terminals that occur in rules.
+    pub terminals: usize,
+    /// The number of symbols that have rules.
+    pub nonterminals: usize,
+    /// The number of nullable symbols.
+    pub nullable: usize,
+    /// The greatest RHS length among rules.
+    pub max_rhs_len: usize,
+    /// The average RHS length of rules, or 0 if there are none.
+    pub avg_rhs_len: f64,
+    /// Whether the grammar has no cycles among unit derivations.
+    pub cycle_free: bool,
+    /// Counts of recursive rules.
+    pub recursion: RecursionSummary,
+}
+
+impl GrammarStats {
+    /// Computes metrics of the grammar.
+    pub fn new<G>(grammar: &G) -> Self
+    where
+        G: RuleContainer,
+    {
+        let terminal_set = SymbolBitSet::terminal_set(grammar);
+        let used = SymbolBitSet::used(grammar);
+        let unit_derivation = derivation::unit_derivation_matrix(grammar);
+
+        let mut rules = 0;
+        let mut total_rhs_len = 0;
+        let mut max_rhs_len = 0;
+        for rule in grammar.rules() {
+            rules += 1;
+            total_rhs_len += rule.rhs.len();
+            max_rhs_len = max_rhs_len.max(rule.rhs.len());
+        }
+        let terminals = used.iter().filter(|&sym| terminal_set.has_sym(sym)).count();
+        GrammarStats {
+            symbols: grammar.num_syms(),
+            rules,
+            terminals,
+            nonterminals: used.count() - terminals,
+            nullable: SymbolBitSet::nullable_set(grammar).count(),
+            max_rhs_len,
+            avg_rhs_len: if rules == 0 {
+                0.0
+            } else {
+                total_rhs_len as f64 / rules as f64
+            },
+            cycle_free: (0..grammar.num_syms()).all(|i| !unit_derivation[(i, i)]),
+            recursion: Recursion::new(grammar).summary(),
+        }
+    }
+}
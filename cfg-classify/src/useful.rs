@@ -1,11 +1,15 @@
 //! Analysis of rule usefulness.
 
+use std::collections::BTreeMap;
+
 use bit_matrix::BitMatrix;
 use bit_vec::BitVec;
 
-use crate::derivation;
+use cfg_grammar::derivation;
 use cfg_grammar::rhs_closure::RhsClosure;
+use cfg_grammar::rule::cfg_rule::CfgRule;
 use cfg_grammar::rule::RuleRef;
+use cfg_grammar::symbol::remap::Remap;
 use cfg_grammar::symbol::set::SymbolBitSet;
 use cfg_grammar::RuleContainer;
 use cfg_symbol::Symbol;
@@ -55,6 +59,16 @@ impl<R> UselessRule<R> {
 }
 
 impl RuleUsefulness {
+    /// Checks whether the rule is reachable.
+    pub fn is_reachable(&self) -> bool {
+        self.reachable
+    }
+
+    /// Checks whether the rule is productive.
+    pub fn is_productive(&self) -> bool {
+        self.productive
+    }
+
     fn is_useless(&self) -> bool {
         !self.reachable || !self.productive
     }
@@ -75,16 +89,7 @@ fn used_syms<'a, G>(grammar: &'a G) -> BitVec
 where
     G: RuleContainer,
 {
-    let num_syms = grammar.sym_source().num_syms();
-    let mut used_syms = BitVec::from_elem(num_syms, false);
-
-    for rule in grammar.rules() {
-        used_syms.set(rule.lhs.usize(), true);
-        for &sym in rule.rhs {
-            used_syms.set(sym.usize(), true);
-        }
-    }
-    used_syms
+    SymbolBitSet::used(grammar).into_bit_vec()
 }
 
 /// Returns the set of productive symbols.
@@ -199,6 +204,36 @@ where
             self.grammar.retain(rule_is_useful);
         }
     }
+
+    /// Removes useless rules. Returns copies of the removed rules, together with
+    /// the reason for their uselessness.
+    pub fn remove_useless_rules_with_diagnostics(&mut self) -> Vec<(CfgRule, RuleUsefulness)> {
+        let removed = self
+            .useless_rules()
+            .map(|useless| {
+                let rule = useless.rule;
+                let rule = CfgRule::new(rule.lhs, rule.rhs.to_vec(), rule.history_id);
+                (rule, useless.usefulness)
+            })
+            .collect();
+        self.remove_useless_rules();
+        removed
+    }
+
+    /// Removes useless rules, then densely renumbers the symbols that remain in use.
+    /// Returns the mapping from old symbols to new symbols.
+    pub fn remove_useless_rules_and_compact(mut self) -> BTreeMap<Symbol, Symbol> {
+        self.remove_useless_rules();
+        let mut remap = Remap::new(self.grammar);
+        remap.remove_unused_symbols();
+        let mapping = remap.get_mapping();
+        mapping
+            .to_internal
+            .iter()
+            .enumerate()
+            .filter_map(|(id, &internal)| internal.map(|internal| (Symbol::from(id), internal)))
+            .collect()
+    }
 }
 
 impl<'a, G, I> Iterator for UselessRules<'a, G, I>
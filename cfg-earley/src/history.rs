@@ -5,7 +5,7 @@ use cfg_symbol::Symbol;
 
 use cfg_grammar::{
     history::node::{BinarizedRhsSubset, LinkedHistoryNode, RootHistoryNode},
-    HistoryNode, RuleContainer,
+    HistoryId, HistoryNode, RuleContainer,
 };
 
 use super::BinarizedGrammar;
@@ -56,10 +56,50 @@ impl BinarizedGrammar {
         }
         result
     }
+
+    /// Returns the events at the three dot positions of a binarized rule, or `None` for
+    /// positions that the rule's history has no dot for. Only the rule's own chain of
+    /// history is processed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule index is out of bounds.
+    pub fn rule_events(&self, rule_idx: usize) -> [Option<Event>; 3] {
+        let rule = self
+            .rules()
+            .nth(rule_idx)
+            .expect("rule index out of bounds");
+        let history = self.history_of(rule.history_id);
+        [0, 1, 2].map(|n| history.dots.get(n).map(|dot| dot.event_without_tracing()))
+    }
+
+    fn history_of(&self, mut history_id: HistoryId) -> History {
+        let graph = self.history_graph();
+        let mut chain = vec![];
+        while let HistoryNode::Linked { prev, ref node } = graph[history_id.get()] {
+            chain.push(node);
+            history_id = prev;
+        }
+        let root = match graph[history_id.get()] {
+            HistoryNode::Root(root) => root,
+            HistoryNode::Linked { .. } => unreachable!(),
+        };
+        chain
+            .into_iter()
+            .rev()
+            .fold(process_root(root), |history, node| match *node {
+                LinkedHistoryNode::Merged { other } => history.merge(&self.history_of(other)),
+                _ => process_linked(node, history),
+            })
+    }
 }
 
 fn process_node(node: &HistoryNode, prev_histories: &[History]) -> History {
     match node {
+        &HistoryNode::Linked {
+            prev,
+            node: LinkedHistoryNode::Merged { other },
+        } => prev_histories[prev.get()].merge(&prev_histories[other.get()]),
         &HistoryNode::Linked {
             prev,
             node: ref linked_node,
@@ -97,6 +137,13 @@ fn process_linked(linked_node: &LinkedHistoryNode, mut prev_history: History) ->
             prev_history
         }
         &LinkedHistoryNode::Distances { .. } => prev_history,
+        &LinkedHistoryNode::Label { .. } => prev_history,
+        &LinkedHistoryNode::Factor { pos, tail } => prev_history.factor(pos, tail),
+        &LinkedHistoryNode::FactorSubsequence { pos, len, inner } => {
+            prev_history.factor_subsequence(pos, len, inner)
+        }
+        // Merging needs the other history, so callers handle it.
+        &LinkedHistoryNode::Merged { .. } => prev_history,
     }
 }
 
@@ -123,6 +170,21 @@ impl RuleDot {
         }
     }
 
+    /// Combines two dots at the same rule position, for example from different derivations
+    /// in an ambiguous forest. The smaller of the two distances wins, and a missing distance
+    /// is replaced with the other one. The event of `self` is kept when present; otherwise,
+    /// the event of `other` is taken.
+    pub fn merge(self, other: RuleDot) -> RuleDot {
+        let distance = match (self.distance, other.distance) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        RuleDot {
+            event: self.event.or(other.event),
+            distance,
+        }
+    }
+
     pub fn trace(self) -> Option<ExternalDottedRule> {
         self.event.map(|x| x.1)
     }
@@ -164,6 +226,25 @@ impl History {
         self.dots[n]
     }
 
+    /// Combines this history with the history of another rule that shares the same rule,
+    /// such as an intermediate rule created once for rules with equal RHS prefixes. Dots
+    /// are combined with `RuleDot::merge`. Otherwise, this history takes precedence.
+    pub fn merge(&self, other: &History) -> Self {
+        let none = RuleDot::none();
+        let len = self.dots.len().max(other.dots.len());
+        let dots = (0..len).map(|i| {
+            let dot = self.dots.get(i).copied().unwrap_or(none);
+            dot.merge(other.dots.get(i).copied().unwrap_or(none))
+        });
+        History {
+            dots: dots.collect::<Vec<_>>().into(),
+            origin: self.origin.or(other.origin),
+            nullable: self.nullable.or(other.nullable),
+            weight: self.weight.or(other.weight),
+            sequence: self.sequence.or(other.sequence),
+        }
+    }
+
     fn binarize(&self, depth: u32) -> Self {
         let none = RuleDot::none();
         let dots = if self.dots.is_empty() {
@@ -179,7 +260,8 @@ impl History {
                     [self.dots[0], none, none]
                 }
             } else {
-                [none, self.dots[dot_len - 2 - depth as usize], none]
+                let dot = dot_len.checked_sub(2 + depth as usize);
+                [none, dot.map_or(none, |i| self.dots[i]), none]
             }
         };
 
@@ -192,6 +274,82 @@ impl History {
         }
     }
 
+    fn factor(&self, pos: u32, tail: bool) -> Self {
+        if self.dots.is_empty() {
+            return self.clone();
+        }
+        let pos = pos as usize;
+        let none = RuleDot::none();
+        let last = self.dots.len() - 1;
+        if pos + 1 > last {
+            // The history has no dots for the symbols around the position, for example
+            // when it has no `Rhs` node.
+            let len = if tail { 2 } else { pos + 2 };
+            return History {
+                origin: if tail { None } else { self.origin },
+                dots: vec![none; len].into(),
+                ..self.clone()
+            };
+        }
+        if tail {
+            // The dots at both ends of the tail are owned by the head.
+            let dots = iter::once(none)
+                .chain(self.dots[pos + 1..last].iter().cloned())
+                .chain(iter::once(none));
+            History {
+                origin: None,
+                dots: dots.collect::<Vec<_>>().into(),
+                ..self.clone()
+            }
+        } else {
+            let dots = self.dots[..=pos]
+                .iter()
+                .cloned()
+                .chain(iter::once(self.dots[last]));
+            History {
+                dots: dots.collect::<Vec<_>>().into(),
+                ..self.clone()
+            }
+        }
+    }
+
+    fn factor_subsequence(&self, pos: u32, len: u32, inner: bool) -> Self {
+        if self.dots.is_empty() {
+            return self.clone();
+        }
+        let (pos, len) = (pos as usize, len as usize);
+        let none = RuleDot::none();
+        if pos + len > self.dots.len() - 1 {
+            // The history has no dots for the symbols of the subsequence.
+            let dots = if inner { len + 1 } else { pos + 2 };
+            return History {
+                origin: if inner { None } else { self.origin },
+                dots: vec![none; dots].into(),
+                ..self.clone()
+            };
+        }
+        if inner {
+            // The dots at both ends of the subsequence are owned by the outer rule.
+            let dots = iter::once(none)
+                .chain(self.dots[pos + 1..pos + len].iter().cloned())
+                .chain(iter::once(none));
+            History {
+                origin: None,
+                dots: dots.collect::<Vec<_>>().into(),
+                ..self.clone()
+            }
+        } else {
+            let dots = self.dots[..=pos]
+                .iter()
+                .chain(&self.dots[pos + len..])
+                .cloned();
+            History {
+                dots: dots.collect::<Vec<_>>().into(),
+                ..self.clone()
+            }
+        }
+    }
+
     fn eliminate_nulling(
         &self,
         rhs0: Symbol,
@@ -4,6 +4,6 @@ pub mod random;
 mod weight;
 mod weighted_rhs_by_lhs;
 
-pub use self::random::{NegativeRule, Random};
+pub use self::random::{DerivationTree, NegativeRule, Random};
 pub use self::weight::Weight;
 pub use self::weighted_rhs_by_lhs::WeightedRhsByLhs;
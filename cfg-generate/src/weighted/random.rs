@@ -3,7 +3,7 @@
 use std::collections::BTreeMap;
 
 use cfg_grammar::BinarizedCfg;
-use cfg_symbol::Symbol;
+use cfg_symbol::{Symbol, SymbolSource};
 // use log::debug;
 use rpds::List;
 
@@ -29,6 +29,14 @@ pub trait Random {
         to_char: F,
     ) -> Result<(Vec<Symbol>, Vec<char>), RandomGenError>;
 
+    /// Generates a random derivation tree. Negative rules are not supported.
+    fn random_with_tree<R: GenRange>(
+        &self,
+        start: Symbol,
+        limit: Option<u64>,
+        rng: &mut R,
+    ) -> Result<DerivationTree, RandomGenError>;
+
     fn with_thread_rng<F: Fn(Symbol, &mut ThreadRng) -> Option<char>>(
         &self,
         start: Symbol,
@@ -41,6 +49,51 @@ pub trait Random {
     }
 }
 
+/// A node of a derivation tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DerivationTree {
+    /// The symbol at this node.
+    pub sym: Symbol,
+    /// Subtrees for the RHS of the rule that was applied to the symbol. Empty for terminals
+    /// and nulling symbols.
+    pub children: Vec<DerivationTree>,
+}
+
+impl DerivationTree {
+    /// Formats the tree as an S-expression, such as `(expr (term a) + (term b))`. Leaves are
+    /// written without parentheses. Symbols without a name are written as `g` followed by
+    /// their number.
+    pub fn to_sexpr(&self, sym_source: &SymbolSource) -> String {
+        let mut result = String::new();
+        // Either a subtree to write, or a closing parenthesis.
+        let mut work = vec![Some(self)];
+        while let Some(item) = work.pop() {
+            let tree = match item {
+                Some(tree) => tree,
+                None => {
+                    result.push(')');
+                    continue;
+                }
+            };
+            if !result.is_empty() && !result.ends_with('(') {
+                result.push(' ');
+            }
+            if !tree.children.is_empty() {
+                result.push('(');
+            }
+            match sym_source.name_of(tree.sym) {
+                Some(name) => result.push_str(name),
+                None => result.push_str(&format!("g{}", tree.sym.usize())),
+            }
+            if !tree.children.is_empty() {
+                work.push(None);
+                work.extend(tree.children.iter().rev().map(Some));
+            }
+        }
+        result
+    }
+}
+
 #[derive(Clone)]
 pub struct ByteSource<I: Iterator<Item = u8>>(I, Vec<u8>);
 
@@ -193,6 +246,45 @@ impl Random for BinarizedCfg {
         }
         Ok((result, string))
     }
+
+    fn random_with_tree<R: GenRange>(
+        &self,
+        start: Symbol,
+        limit: Option<u64>,
+        rng: &mut R,
+    ) -> Result<DerivationTree, RandomGenError> {
+        let weighted = self.weighted();
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        let mut work = vec![start];
+        // Symbols in preorder, each with the number of its children.
+        let mut preorder = vec![];
+        let mut num_terminals = 0;
+        while let Some(sym) = work.pop() {
+            if terminal_set.has_sym(sym) {
+                preorder.push((sym, 0));
+                num_terminals += 1;
+                if let Some(max_terminals) = limit {
+                    if num_terminals > max_terminals {
+                        return Err(RandomGenError::LimitExceeded);
+                    }
+                }
+            } else {
+                let rhs = weighted.pick_rhs(sym, rng);
+                preorder.push((sym, rhs.len()));
+                work.extend(rhs.iter().cloned().rev());
+            }
+        }
+        // Build the tree bottom-up.
+        let mut subtrees: Vec<DerivationTree> = vec![];
+        for (sym, num_children) in preorder.into_iter().rev() {
+            let children = subtrees.split_off(subtrees.len() - num_children);
+            subtrees.push(DerivationTree {
+                sym,
+                children: children.into_iter().rev().collect(),
+            });
+        }
+        Ok(subtrees.pop().expect("derivation tree is empty"))
+    }
 }
 
 #[test]
@@ -210,3 +302,45 @@ fn test_simplest_random_generation() {
     let string = binarized.with_thread_rng(lhs, Some(1), &[], to_char);
     assert_eq!(string, Ok((vec![rhs], vec!['X'])));
 }
+
+#[test]
+fn test_random_derivation_tree() {
+    use cfg_grammar::{Cfg, RuleContainer};
+
+    let mut grammar = Cfg::new();
+    let [expr, term, sum, plus, a] = grammar.named_syms(["expr", "term", "sum", "+", "a"]);
+    grammar
+        .rule(expr)
+        .rhs([term, sum])
+        .rule(sum)
+        .rhs([plus, term])
+        .rule(term)
+        .rhs([a]);
+    let binarized = grammar.binarize();
+
+    let tree = binarized.random_with_tree(expr, Some(10), &mut thread_rng());
+    let tree = tree.expect("generation failed");
+    let leaf = |sym| DerivationTree {
+        sym,
+        children: vec![],
+    };
+    let term_tree = DerivationTree {
+        sym: term,
+        children: vec![leaf(a)],
+    };
+    let expected = DerivationTree {
+        sym: expr,
+        children: vec![
+            term_tree.clone(),
+            DerivationTree {
+                sym: sum,
+                children: vec![leaf(plus), term_tree],
+            },
+        ],
+    };
+    assert_eq!(tree, expected);
+    assert_eq!(
+        tree.to_sexpr(binarized.sym_source()),
+        "(expr (term a) (sum + (term a)))"
+    );
+}
@@ -1,7 +1,6 @@
 use std::collections::BTreeMap;
 
-use cfg_grammar::history::node::LinkedHistoryNode;
-use cfg_grammar::{BinarizedCfg, Cfg, HistoryNode, RuleContainer};
+use cfg_grammar::{BinarizedCfg, Cfg, RuleContainer};
 use cfg_symbol::Symbol;
 
 use super::random::GenRange;
@@ -43,51 +42,33 @@ impl<W: Weight> WeightedRhsByLhs<W> {
 }
 
 pub trait Weighted {
+    /// Collects weights of rules. Rules without a weight default to 1.0.
     fn weighted(&self) -> WeightedRhsByLhs<f64>;
 }
 
 impl Weighted for Cfg {
     fn weighted(&self) -> WeightedRhsByLhs<f64> {
-        let mut weighted = WeightedRhsByLhs::new();
-        for rule in self.rules() {
-            let mut history_id = rule.history_id;
-            let mut result = None;
-            while let &HistoryNode::Linked { prev, ref node } =
-                &self.history_graph()[history_id.get()]
-            {
-                if let &LinkedHistoryNode::Weight { weight } = node {
-                    result = Some(weight);
-                    break;
-                }
-                history_id = prev;
-            }
-            weighted.add_weight(result.unwrap_or(1.0), rule.lhs, rule.rhs);
-        }
-        weighted
+        weighted_rules(self)
     }
 }
 
 impl Weighted for BinarizedCfg {
     fn weighted(&self) -> WeightedRhsByLhs<f64> {
-        let mut weighted = WeightedRhsByLhs::new();
-        for rule in self.rules() {
-            let mut history_id = rule.history_id;
-            let mut result = None;
-            while let &HistoryNode::Linked { prev, ref node } =
-                &self.history_graph()[history_id.get()]
-            {
-                if let &LinkedHistoryNode::Weight { weight } = node {
-                    result = Some(weight);
-                    break;
-                }
-                history_id = prev;
-            }
-            weighted.add_weight(result.unwrap_or(1.0), rule.lhs, rule.rhs);
-        }
-        weighted
+        weighted_rules(self)
     }
 }
 
+/// Collects weights of rules. Rules without a weight in their history have a weight of 1.0,
+/// so weighted and unweighted rules can be mixed.
+fn weighted_rules<G: RuleContainer>(grammar: &G) -> WeightedRhsByLhs<f64> {
+    let mut weighted = WeightedRhsByLhs::new();
+    for rule in grammar.rules() {
+        let weight = grammar.history_graph().weight(rule.history_id);
+        weighted.add_weight(weight.unwrap_or(1.0), rule.lhs, rule.rhs);
+    }
+    weighted
+}
+
 impl<W: Weight> WeightedRhsByLhs<W> {
     #[cfg(feature = "rand")]
     pub fn pick_rhs<R>(&self, lhs: Symbol, rng: &mut R) -> &[Symbol]
@@ -113,3 +94,27 @@ impl<W: Weight> WeightedRhsByLhs<W> {
         }
     }
 }
+
+#[test]
+fn test_partially_weighted() {
+    use cfg_grammar::history::node::LinkedHistoryNode;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    let mut grammar = Cfg::new();
+    let [start, x, y] = grammar.sym();
+    grammar
+        .rule(start)
+        .rhs_with_linked_history([x], LinkedHistoryNode::Weight { weight: 3.0 })
+        .rhs([y]);
+    assert!(grammar.has_weights());
+
+    let weighted = grammar.weighted();
+    let mut rng = SmallRng::seed_from_u64(42);
+    let samples = 10_000;
+    let count_x = (0..samples)
+        .filter(|_| weighted.pick_rhs(start, &mut rng) == [x])
+        .count();
+    let ratio = count_x as f64 / samples as f64;
+    assert!((ratio - 0.75).abs() < 0.03, "ratio {}", ratio);
+}
@@ -2,13 +2,14 @@
 //! A binarized grammar contains only such rules.
 
 use std::cmp::{self, Ord, Ordering};
+use std::collections::{BTreeMap, BTreeSet};
 use std::iter;
 use std::mem;
 
 use bit_vec::BitVec;
 
 use crate::history::node::{
-    BinarizedRhsSubset::*, HistoryNodeBinarize, HistoryNodeEliminateNulling,
+    BinarizedRhsSubset::*, HistoryNodeBinarize, HistoryNodeEliminateNulling, LinkedHistoryNode,
 };
 use crate::history::{HistoryGraph, HistoryId, HistoryNode};
 use crate::local_prelude::*;
@@ -95,6 +96,26 @@ impl BinarizedCfg {
         grammar
     }
 
+    /// Creates a BinarizedCfg by binarizing a context-free grammar. Intermediate rules for
+    /// equal prefixes of right-hand sides are created only once. The histories of all rules
+    /// that contribute to such an intermediate rule are recorded with `Merged` history nodes.
+    ///
+    /// Binarization nests rules to the left, so only prefixes are shared. Rules that share
+    /// a suffix, such as `A ::= a b c` and `B ::= x b c`, get separate intermediate rules.
+    pub fn from_context_free_merging<G>(this: &G) -> BinarizedCfg
+    where
+        G: RuleContainer + Default,
+    {
+        let mut grammar = BinarizedCfg::with_sym_source(this.sym_source().clone());
+        grammar.history_graph = this.history_graph().clone();
+        let mut prefixes = BTreeMap::new();
+        for rule in this.rules() {
+            grammar.add_rule_merging(rule.as_rule_ref(), &mut prefixes);
+        }
+
+        grammar
+    }
+
     /// Sorts the rule array.
     pub fn sort(&mut self) {
         self.rules.sort();
@@ -113,6 +134,57 @@ impl BinarizedCfg {
         self.rules.dedup();
     }
 
+    /// Adds a rule, reusing intermediate rules for prefixes found in `prefixes`. The map
+    /// goes from prefixes of right-hand sides to indices of intermediate rules.
+    fn add_rule_merging(&mut self, rule: RuleRef, prefixes: &mut BTreeMap<Vec<Symbol>, usize>) {
+        if rule.rhs.len() <= 2 {
+            self.add_rule(rule);
+            return;
+        }
+        let len = rule.rhs.len();
+        // The symbol that derives the prefix processed so far.
+        let mut left = rule.rhs[0];
+        for prefix_len in 2..len {
+            let history_id = self.add_history_node(
+                HistoryNodeBinarize {
+                    prev: rule.history_id,
+                    depth: (len - prefix_len) as u32,
+                }
+                .into(),
+            );
+            let prefix = &rule.rhs[..prefix_len];
+            if let Some(&rule_idx) = prefixes.get(prefix) {
+                let existing = &mut self.rules[rule_idx];
+                existing.history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+                    prev: existing.history_id,
+                    node: LinkedHistoryNode::Merged { other: history_id },
+                });
+                left = existing.lhs;
+            } else {
+                let lhs = self.sym_source.next_sym();
+                prefixes.insert(prefix.to_vec(), self.rules.len());
+                self.rules.push(BinarizedRule {
+                    lhs,
+                    rhs: Two([left, prefix[prefix_len - 1]]),
+                    history_id,
+                });
+                left = lhs;
+            }
+        }
+        let history_id = self.add_history_node(
+            HistoryNodeBinarize {
+                prev: rule.history_id,
+                depth: 0,
+            }
+            .into(),
+        );
+        self.rules.push(BinarizedRule {
+            lhs: rule.lhs,
+            rhs: Two([left, rule.rhs[len - 1]]),
+            history_id,
+        });
+    }
+
     /// Returns generated symbols.
     pub fn sym<const N: usize>(&mut self) -> [Symbol; N] {
         self.sym_source_mut().sym()
@@ -218,6 +290,83 @@ impl BinarizedCfg {
 
         nulling_grammar
     }
+
+    /// Eliminates all rules of the form `A ::= epsilon`, like `eliminate_nulling_rules`.
+    /// Additionally returns the symbols that had non-nulling rules before elimination, but
+    /// lost all of them, because they became unproductive.
+    pub fn eliminate_nulling_rules_reporting(&mut self) -> (BinarizedCfg, Vec<Symbol>) {
+        let lhs_before: BTreeSet<Symbol> = self.rules.iter().map(|rule| rule.lhs).collect();
+        let nulling_grammar = self.eliminate_nulling_rules();
+        let lhs_after: BTreeSet<Symbol> = self.rules.iter().map(|rule| rule.lhs).collect();
+        let lost = lhs_before.difference(&lhs_after).cloned().collect();
+        (nulling_grammar, lost)
+    }
+
+    /// Adds rules of a nulling grammar returned by `eliminate_nulling_rules` back into this
+    /// grammar, which then derives the empty string where it did before elimination. Rules
+    /// that are already present are not duplicated. Rewritten rules are kept.
+    pub fn reinsert_nulling(&mut self, nulling: &BinarizedCfg) {
+        // Histories of nulling rules still refer to this grammar's history graph.
+        for (sym_id, &history) in nulling.nulling.iter().enumerate() {
+            if let Some(history_id) = history {
+                if self.nulling.len() <= sym_id {
+                    self.nulling.resize(sym_id + 1, None);
+                }
+                self.nulling[sym_id].get_or_insert(history_id);
+            }
+        }
+        for rule in &nulling.rules {
+            let present = self
+                .rules
+                .iter()
+                .any(|existing| existing.lhs == rule.lhs && existing.rhs == rule.rhs);
+            if !present {
+                let node = nulling.history_graph[rule.history_id.get()].clone();
+                let history_id = self.history_graph.add_history_node(node);
+                self.rules.push(BinarizedRule {
+                    history_id,
+                    ..*rule
+                });
+            }
+        }
+    }
+
+    /// Maps nulled positions in the main grammar to rules of this nulling grammar. Call this
+    /// method on the grammar returned by `eliminate_nulling_rules`.
+    ///
+    /// Keys are pairs of a rule's index in `main` and the position in its RHS before
+    /// elimination. Values are indices of this grammar's rules that derive the nulled symbol.
+    pub fn apply_nulling(&self, main: &BinarizedCfg) -> BTreeMap<(usize, usize), Vec<usize>> {
+        let mut result = BTreeMap::new();
+        for (rule_idx, rule) in main.indexed_rules() {
+            let mut history_id = rule.history_id;
+            while let HistoryNode::Linked { prev, ref node } = main.history_graph[history_id.get()]
+            {
+                if let LinkedHistoryNode::EliminateNulling {
+                    rhs0, rhs1, which, ..
+                } = *node
+                {
+                    let nulled = match which {
+                        Left => Some((0, rhs0)),
+                        Right => rhs1.map(|rhs1| (1, rhs1)),
+                        All => None,
+                    };
+                    if let Some((position, sym)) = nulled {
+                        let nulling_rules = self
+                            .rules()
+                            .enumerate()
+                            .filter(|(_, nulling_rule)| nulling_rule.lhs == sym)
+                            .map(|(nulling_idx, _)| nulling_idx)
+                            .collect();
+                        result.insert((rule_idx, position), nulling_rules);
+                    }
+                    break;
+                }
+                history_id = prev;
+            }
+        }
+        result
+    }
 }
 
 impl RuleContainer for BinarizedCfg {
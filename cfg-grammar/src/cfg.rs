@@ -1,6 +1,22 @@
+use std::collections::hash_map::{DefaultHasher, Entry};
+use std::collections::{btree_map, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
+
+use bit_matrix::BitMatrix;
+
+use crate::derivation;
+use crate::history::node::{HistoryNodeRhs, HistoryNodeWeight, LinkedHistoryNode, RootHistoryNode};
 use crate::history::{HistoryGraph, HistoryId, HistoryNode};
+use crate::precedenced_rule::PrecedenceError;
+use crate::prefix_trie::PrefixTrie;
+use crate::railroad::RailNode;
+use crate::rhs_closure::{RhsClosure, RhsPropertyMode};
+use crate::rule::builder::RuleBuilder;
 use crate::rule::cfg_rule::CfgRule;
 use crate::rule::RuleRef;
+use crate::symbol::set::SymbolBitSet;
 use crate::BinarizedCfg;
 use crate::{local_prelude::*, AsRuleRef};
 
@@ -13,6 +29,44 @@ pub struct Cfg {
     rules: Vec<CfgRule>,
     /// History container.
     history_graph: HistoryGraph,
+    /// Revision counter, bumped whenever rules change.
+    revision: u64,
+    /// Whether binarization merges equal intermediate rules.
+    merge_histories_on_dedup: bool,
+}
+
+/// The error returned when a grammar cannot be given to an Earley recognizer as is.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EarleyReadyError {
+    /// A rule has more than two RHS symbols.
+    NotBinary {
+        /// The index of the rule.
+        rule: usize,
+        /// The left-hand side of the rule.
+        lhs: Symbol,
+        /// The right-hand side of the rule.
+        rhs: Vec<Symbol>,
+    },
+    /// A rule has an empty RHS.
+    Nulling {
+        /// The index of the rule.
+        rule: usize,
+        /// The left-hand side of the rule.
+        lhs: Symbol,
+    },
+}
+
+/// The kinds of symbols on a rule's RHS. Terminals are symbols that never appear on a LHS.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RhsKind {
+    /// The RHS is empty.
+    Empty,
+    /// The RHS consists only of terminals.
+    PurelyTerminal,
+    /// The RHS consists only of nonterminals.
+    PurelyNonterminal,
+    /// The RHS contains both terminals and nonterminals.
+    Mixed,
 }
 
 impl Default for Cfg {
@@ -33,8 +87,22 @@ impl Cfg {
             sym_source: sym_source,
             rules: vec![],
             history_graph: HistoryGraph::new(),
+            revision: 0,
+            merge_histories_on_dedup: false,
         }
     }
+
+    /// Creates a context-free grammar with the rules of a binarized grammar. Binary rules
+    /// are kept as they are. The symbol source and history graph are cloned, so symbols and
+    /// history ids remain valid in the new grammar.
+    pub fn from_binarized(bin: &BinarizedCfg) -> Self {
+        let mut cfg = Self::with_sym_source(bin.sym_source().clone());
+        cfg.history_graph = bin.history_graph().clone();
+        for rule in bin.rules() {
+            cfg.add_rule(rule);
+        }
+        cfg
+    }
 }
 
 impl Cfg {
@@ -43,6 +111,17 @@ impl Cfg {
         self.sym_source_mut().sym()
     }
 
+    /// Returns generated symbols with the given names.
+    pub fn named_syms<const N: usize>(&mut self, names: [&str; N]) -> [Symbol; N] {
+        self.sym_source_mut().with_names(names)
+    }
+
+    /// Starts building a new rule with the LHS of the given name. Symbols are looked up by
+    /// name, and created on first use.
+    pub fn rule_str(&mut self, lhs: &str) -> RuleBuilder<&mut Self> {
+        RuleBuilder::new(self).rule_str(lhs)
+    }
+
     /// Generates a new unique symbol.
     pub fn next_sym(&mut self) -> Symbol {
         self.sym_source_mut().next_sym()
@@ -53,12 +132,1142 @@ impl Cfg {
         self.sym_source().num_syms()
     }
 
+    /// Returns the revision of this grammar's rules. The revision changes whenever
+    /// rules are added, removed, reordered or rewritten. Calls that leave the rules
+    /// unchanged keep the revision.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Removes and returns the rule at the given index, or `None` if the index is out of bounds.
+    /// Rules that follow are shifted down by one.
+    pub fn remove_rule(&mut self, idx: usize) -> Option<CfgRule> {
+        if idx < self.rules.len() {
+            self.revision += 1;
+            Some(self.rules.remove(idx))
+        } else {
+            None
+        }
+    }
+
+    /// Retains only the rules for which `f` returns true, while giving `f` mutable access to
+    /// every rule, so that surviving rules can be modified in the same pass.
+    ///
+    /// Rule histories may describe the original RHS, for example its length in an `Rhs` node.
+    /// A changed RHS should keep its length, or get a history that matches it.
+    pub fn retain_map<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut CfgRule) -> bool,
+    {
+        let mut changed = false;
+        self.rules.retain_mut(|rule| {
+            let (lhs, rhs) = (rule.lhs, rule.rhs.clone());
+            let keep = f(rule);
+            changed |= !keep || rule.lhs != lhs || rule.rhs != rhs;
+            keep
+        });
+        if changed {
+            self.revision += 1;
+        }
+    }
+
+    /// Removes all rules that satisfy the predicate and returns them in their original order.
+    pub fn drain_rules_where<F>(&mut self, mut f: F) -> Vec<CfgRule>
+    where
+        F: FnMut(&CfgRule) -> bool,
+    {
+        let (removed, kept): (Vec<_>, Vec<_>) = mem::take(&mut self.rules)
+            .into_iter()
+            .partition(|rule| f(rule));
+        self.rules = kept;
+        if !removed.is_empty() {
+            self.revision += 1;
+        }
+        removed
+    }
+
+    /// Returns owned copies of the RHS of every rule with the given LHS. The result is empty
+    /// for terminals and symbols without rules.
+    pub fn productions_of(&self, lhs: Symbol) -> Vec<Vec<Symbol>> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.lhs == lhs)
+            .map(|rule| rule.rhs.clone())
+            .collect()
+    }
+
+    /// Returns the number of rules with the given LHS. Every alternative is a separate rule,
+    /// so each call to `RuleBuilder::rhs` adds one, even if it repeats an earlier RHS.
+    pub fn alternatives_count(&self, lhs: Symbol) -> usize {
+        self.rules.iter().filter(|rule| rule.lhs == lhs).count()
+    }
+
+    /// Returns the derivation closure of the grammar. For symbols A and B, the entry
+    /// at `(A.usize(), B.usize())` is set if A derives a string that contains B, that is
+    /// `A ⟹* α B β`. Every symbol derives itself.
+    pub fn derivation_closure(&self) -> BitMatrix {
+        derivation::reachability_matrix(self)
+    }
+
+    /// Returns the given roots that are not reachable from any other of the given roots,
+    /// in their original order. Of roots that reach one another, only the first is kept.
+    pub fn minimal_roots(&self, roots: &[Symbol]) -> Vec<Symbol> {
+        let closure = self.derivation_closure();
+        let reaches = |a: Symbol, b: Symbol| closure[(a.usize(), b.usize())];
+        let mut result: Vec<Symbol> = vec![];
+        for (i, &root) in roots.iter().enumerate() {
+            let redundant = roots.iter().enumerate().any(|(j, &other)| {
+                other != root && reaches(other, root) && (j < i || !reaches(root, other))
+            });
+            if !redundant && !result.contains(&root) {
+                result.push(root);
+            }
+        }
+        result
+    }
+
+    /// Returns the length of the longest RHS prefix shared by all rules with the given LHS.
+    /// With a single rule, this is the length of its RHS. Returns 0 if there are no rules.
+    pub fn rules_common_prefix(&self, lhs: Symbol) -> usize {
+        let mut rules = self.rules.iter().filter(|rule| rule.lhs == lhs);
+        let first = match rules.next() {
+            Some(rule) => rule,
+            None => return 0,
+        };
+        rules.fold(first.rhs.len(), |len, rule| {
+            len.min(self.common_prefix(first, rule))
+        })
+    }
+
+    /// Returns the length of the longest common prefix of two rules' right-hand sides.
+    pub fn common_prefix<R: AsRuleRef>(&self, rule_a: R, rule_b: R) -> usize {
+        let (rule_a, rule_b) = (rule_a.as_rule_ref(), rule_b.as_rule_ref());
+        rule_a
+            .rhs
+            .iter()
+            .zip(rule_b.rhs)
+            .take_while(|(a, b)| a == b)
+            .count()
+    }
+
+    /// Groups rules built by a precedenced rule builder by their looseness. Returns a map from
+    /// looseness levels to indices of rules with an `AssignPrecedence` node in their history.
+    pub fn precedence_levels(&self) -> BTreeMap<u32, Vec<usize>> {
+        let mut levels: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if let Some(looseness) = self.history_graph.precedence(rule.history_id) {
+                levels.entry(looseness).or_default().push(idx);
+            }
+        }
+        levels
+    }
+
+    /// Checks that rules built by precedenced rule builders form proper precedence ladders.
+    /// All rules of a level must have the same looseness, and every level except the tightest
+    /// one must have a unit rule that leads to the next tighter level. Levels without any
+    /// alternatives are skipped over.
+    pub fn validate_precedence(&self) -> Result<(), PrecedenceError> {
+        let mut looseness_of: BTreeMap<Symbol, u32> = BTreeMap::new();
+        for (&looseness, rule_indices) in &self.precedence_levels() {
+            for &idx in rule_indices {
+                let lhs = self.rules[idx].lhs;
+                if *looseness_of.entry(lhs).or_insert(looseness) != looseness {
+                    return Err(PrecedenceError::MixedLooseness { lhs });
+                }
+            }
+        }
+        // Unit rules without assigned precedence link levels together.
+        let link = |sym: Symbol| {
+            self.rules
+                .iter()
+                .find(|rule| {
+                    rule.lhs == sym
+                        && rule.rhs.len() == 1
+                        && self.history_graph.precedence(rule.history_id).is_none()
+                })
+                .map(|rule| rule.rhs[0])
+        };
+        for (&lhs, &looseness) in &looseness_of {
+            let error = PrecedenceError::MissingTighterLevel { lhs, looseness };
+            let mut current = lhs;
+            let mut expected = looseness;
+            while expected > 0 {
+                expected -= 1;
+                current = link(current).ok_or(error)?;
+                match looseness_of.get(&current) {
+                    Some(&found) if found == expected => break,
+                    Some(_) => return Err(error),
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether any rule has a weight in its history.
+    pub fn has_weights(&self) -> bool {
+        self.rules
+            .iter()
+            .any(|rule| self.history_graph.weight(rule.history_id).is_some())
+    }
+
+    /// Sets rule weights from observed counts, keyed by rule index. Counts are normalized
+    /// over the rules of each LHS, so that the weights of its alternatives sum to 1. Rules
+    /// that have no count or a count of zero are smoothed with a count of 0.1.
+    pub fn set_weights_from_counts(&mut self, counts: &BTreeMap<usize, u64>) {
+        const SMOOTHING: f64 = 0.1;
+        let count_of = |idx: usize| match counts.get(&idx) {
+            Some(&count) if count > 0 => count as f64,
+            _ => SMOOTHING,
+        };
+        let mut totals: BTreeMap<Symbol, f64> = BTreeMap::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            *totals.entry(rule.lhs).or_insert(0.0) += count_of(idx);
+        }
+        for (idx, rule) in self.rules.iter_mut().enumerate() {
+            rule.history_id = self.history_graph.add_history_node(
+                HistoryNodeWeight {
+                    prev: rule.history_id,
+                    weight: count_of(idx) / totals[&rule.lhs],
+                }
+                .into(),
+            );
+        }
+    }
+
+    /// Removes weights from the history of every rule, keeping the rest of its history.
+    /// Nodes that follow a weight in a rule's chain of history are added again without it.
+    pub fn clear_weights(&mut self) {
+        for rule in &mut self.rules {
+            let mut chain = vec![];
+            let mut history_id = rule.history_id;
+            while let HistoryNode::Linked { prev, ref node } = self.history_graph[history_id.get()]
+            {
+                chain.push((prev, node.clone()));
+                history_id = prev;
+            }
+            let is_weight =
+                |node: &LinkedHistoryNode| matches!(node, LinkedHistoryNode::Weight { .. });
+            let oldest_weight = match chain.iter().rposition(|(_, node)| is_weight(node)) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let mut history_id = chain[oldest_weight].0;
+            for (_, node) in chain.drain(..oldest_weight).rev() {
+                if !is_weight(&node) {
+                    history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+                        prev: history_id,
+                        node,
+                    });
+                }
+            }
+            rule.history_id = history_id;
+        }
+    }
+
+    /// Returns the set of nonterminals that derive the empty string and nothing else. Unlike
+    /// nullable symbols in general, these never derive a string that contains a terminal.
+    pub fn epsilon_only_symbols(&self) -> SymbolBitSet {
+        let nullable = SymbolBitSet::nullable_set(self);
+        let mut productive = SymbolBitSet::terminal_set(self).into_bit_vec();
+        productive.or(&nullable.clone().into_bit_vec());
+        RhsClosure::new(self).rhs_closure(&mut productive);
+        let mut non_empty = SymbolBitSet::terminal_set(self);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in &self.rules {
+                if !non_empty.has_sym(rule.lhs)
+                    && rule.rhs.iter().all(|sym| productive[sym.usize()])
+                    && rule.rhs.iter().any(|&sym| non_empty.has_sym(sym))
+                {
+                    non_empty.set(rule.lhs, true);
+                    changed = true;
+                }
+            }
+        }
+        let mut result = nullable;
+        for sym in non_empty.iter() {
+            result.set(sym, false);
+        }
+        result
+    }
+
+    /// Returns symbols that do not occur in any rule.
+    pub fn unused_symbols(&self) -> Vec<Symbol> {
+        let used = SymbolBitSet::used(self);
+        (0..self.num_syms())
+            .map(Symbol::from)
+            .filter(|&sym| !used.has_sym(sym))
+            .collect()
+    }
+
+    /// Returns the distinct symbols that occur on right-hand sides of rules with the given LHS.
+    pub fn rhs_symbols_of(&self, lhs: Symbol) -> BTreeSet<Symbol> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.lhs == lhs)
+            .flat_map(|rule| rule.rhs.iter().cloned())
+            .collect()
+    }
+
+    /// Builds a trie over the right-hand sides of the given symbol's rules. Nodes where
+    /// an alternative ends hold the index of its rule.
+    pub fn rhs_trie(&self, lhs: Symbol) -> PrefixTrie {
+        let mut trie = PrefixTrie::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if rule.lhs == lhs {
+                trie.insert(&rule.rhs[..], idx);
+            }
+        }
+        trie
+    }
+
+    /// Builds a railroad diagram model for every nonterminal. Alternatives become a choice,
+    /// and symbols within an alternative become a sequence. Sequence rules become a repetition
+    /// with their bounds, among the other alternatives of their LHS, and helper symbols of their
+    /// rewrite are left out. Only separators that sequence histories record, which are trailing
+    /// ones, appear in the model.
+    pub fn railroad_model(&self) -> BTreeMap<Symbol, RailNode> {
+        let nonterminals: BTreeSet<Symbol> = self.rules.iter().map(|rule| rule.lhs).collect();
+        let leaf = |sym: Symbol| {
+            if nonterminals.contains(&sym) {
+                RailNode::Nonterminal(sym)
+            } else {
+                RailNode::Terminal(sym)
+            }
+        };
+        let mut alternatives: BTreeMap<Symbol, Vec<RailNode>> = BTreeMap::new();
+        let mut repeats = BTreeSet::new();
+        for rule in &self.rules {
+            let node = match self.history_graph.sequence(rule.history_id) {
+                Some((true, item, sep)) => {
+                    let (min, max) = self.history_graph.sequence_bounds(rule.history_id).unwrap();
+                    // A sequence is rewritten into several rules for its LHS.
+                    if !repeats.insert((rule.lhs, item, sep, min, max)) {
+                        continue;
+                    }
+                    RailNode::Repeat {
+                        item: Box::new(leaf(item)),
+                        separator: sep.map(|sep| Box::new(leaf(sep))),
+                        min,
+                        max,
+                    }
+                }
+                Some((false, _, _)) => continue,
+                None => RailNode::Sequence(rule.rhs.iter().map(|&sym| leaf(sym)).collect()),
+            };
+            alternatives.entry(rule.lhs).or_default().push(node);
+        }
+        alternatives
+            .into_iter()
+            .map(|(lhs, mut alternatives)| {
+                let node = if alternatives.len() == 1 {
+                    alternatives.pop().unwrap()
+                } else {
+                    RailNode::Choice(alternatives)
+                };
+                (lhs, node)
+            })
+            .collect()
+    }
+
+    /// Returns the minimum and maximum RHS length of rules for every LHS.
+    pub fn lhs_arity_spread(&self) -> BTreeMap<Symbol, (usize, usize)> {
+        let mut spread = BTreeMap::new();
+        for rule in &self.rules {
+            let len = rule.rhs.len();
+            let (min, max) = spread.entry(rule.lhs).or_insert((len, len));
+            *min = (*min).min(len);
+            *max = (*max).max(len);
+        }
+        spread
+    }
+
+    /// Counts occurrences of every terminal on right-hand sides of rules, including repeated
+    /// occurrences within a single rule.
+    pub fn terminal_frequencies(&self) -> BTreeMap<Symbol, usize> {
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        let mut frequencies = BTreeMap::new();
+        for rule in &self.rules {
+            for &sym in &rule.rhs {
+                if terminal_set.has_sym(sym) {
+                    *frequencies.entry(sym).or_insert(0) += 1;
+                }
+            }
+        }
+        frequencies
+    }
+
+    /// Returns terminals in order of their first appearance, scanning rules top-to-bottom
+    /// and each right-hand side left-to-right.
+    pub fn terminals_in_appearance_order(&self) -> Vec<Symbol> {
+        let mut pending = SymbolBitSet::terminal_set(self);
+        let mut result = vec![];
+        for rule in &self.rules {
+            for &sym in &rule.rhs {
+                if pending.has_sym(sym) {
+                    pending.set(sym, false);
+                    result.push(sym);
+                }
+            }
+        }
+        result
+    }
+
+    /// Counts the distinct derivation trees of the empty string from the given symbol. Counts
+    /// saturate at `usize::MAX`, which is also returned when there are infinitely many
+    /// derivations, for example through a nullable cycle. A count greater than one means
+    /// that the empty string is derived ambiguously.
+    pub fn nullable_derivation_count(&self, sym: Symbol) -> usize {
+        let lhs_set: BTreeSet<Symbol> = self.rules.iter().map(|rule| rule.lhs).collect();
+        let mut counts = vec![0usize; self.num_syms()];
+        // Finite counts settle within as many passes as there are symbols.
+        let mut passes = 0;
+        loop {
+            let mut changed = false;
+            for &lhs in &lhs_set {
+                let count = self.nullable_count_from(lhs, &counts);
+                if count != counts[lhs.usize()] {
+                    counts[lhs.usize()] = if passes > self.num_syms() {
+                        usize::MAX
+                    } else {
+                        count
+                    };
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+            passes += 1;
+        }
+        counts[sym.usize()]
+    }
+
+    /// Counts derivations of the empty string from `lhs`, given counts for its RHS symbols.
+    fn nullable_count_from(&self, lhs: Symbol, counts: &[usize]) -> usize {
+        self.rules
+            .iter()
+            .filter(|rule| rule.lhs == lhs)
+            .map(|rule| {
+                rule.rhs.iter().fold(1usize, |product, sym| {
+                    product.saturating_mul(counts[sym.usize()])
+                })
+            })
+            .fold(0usize, |sum, count| sum.saturating_add(count))
+    }
+
+    /// Computes one shortest terminal string derivable from every symbol, or `None` for
+    /// unproductive symbols. Terminals derive themselves.
+    pub fn shortest_derivations(&self) -> BTreeMap<Symbol, Option<Vec<Symbol>>> {
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        let mut shortest: Vec<Option<Vec<Symbol>>> = vec![None; self.num_syms()];
+        for terminal in terminal_set.iter() {
+            shortest[terminal.usize()] = Some(vec![terminal]);
+        }
+        // Keep going for as long as any string was shortened in the last pass.
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in &self.rules {
+                let candidate = rule.rhs.iter().try_fold(vec![], |mut string, sym| {
+                    string.extend(shortest[sym.usize()].as_ref()?.iter().cloned());
+                    Some(string)
+                });
+                if let Some(candidate) = candidate {
+                    let current = &mut shortest[rule.lhs.usize()];
+                    match current {
+                        Some(string) if string.len() <= candidate.len() => {}
+                        _ => {
+                            *current = Some(candidate);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        shortest
+            .into_iter()
+            .enumerate()
+            .map(|(id, string)| (Symbol::from(id), string))
+            .collect()
+    }
+
+    /// Computes the minimal number of rule applications needed to reach every symbol from the
+    /// root, with a breadth-first search. The root has depth 0. Symbols that are unreachable
+    /// from the root are omitted.
+    pub fn derivation_depths(&self, root: Symbol) -> BTreeMap<Symbol, u32> {
+        let mut depths = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        depths.insert(root, 0);
+        queue.push_back(root);
+        while let Some(lhs) = queue.pop_front() {
+            let depth = depths[&lhs];
+            for rule in self.rules.iter().filter(|rule| rule.lhs == lhs) {
+                for &sym in &rule.rhs {
+                    if let btree_map::Entry::Vacant(entry) = depths.entry(sym) {
+                        entry.insert(depth + 1);
+                        queue.push_back(sym);
+                    }
+                }
+            }
+        }
+        depths
+    }
+
+    /// Returns the rule at the given position in the order of rule iteration, or `None` if the
+    /// index is out of bounds. Indices are only stable until the grammar is modified.
+    pub fn rule_at(&self, index: usize) -> Option<RuleRef<'_>> {
+        self.rules.get(index).map(|rule| rule.as_rule_ref())
+    }
+
+    /// Returns the number of rules.
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns the LHS and RHS of the rule at the given index, ignoring its history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn rule_signature(&self, idx: usize) -> (Symbol, Rc<[Symbol]>) {
+        let rule = &self.rules[idx];
+        (rule.lhs, rule.rhs.as_slice().into())
+    }
+
+    /// Groups indices of rules that have identical LHS and RHS, ignoring history. Groups are
+    /// ordered by their first rule, and every rule belongs to exactly one group.
+    pub fn equivalent_rules(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_of: HashMap<_, usize> = HashMap::new();
+        for idx in 0..self.rules.len() {
+            match group_of.entry(self.rule_signature(idx)) {
+                Entry::Occupied(entry) => groups[*entry.get()].push(idx),
+                Entry::Vacant(entry) => {
+                    entry.insert(groups.len());
+                    groups.push(vec![idx]);
+                }
+            }
+        }
+        groups
+    }
+
+    /// Computes a hash of the grammar's rules that ignores histories, rule order and symbol
+    /// numbering, so that grammars which differ only by renaming symbols hash equal. Symbols
+    /// that occur in no rule do not affect the result.
+    ///
+    /// Symbols are labeled by repeatedly hashing the rules they occur in, until the labels
+    /// stop distinguishing more symbols. The result is stable within a single build.
+    pub fn content_hash(&self) -> u64 {
+        let used = SymbolBitSet::used(self);
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        let mut labels: Vec<u64> = (0..self.num_syms())
+            .map(|i| hash_one(terminal_set.has_sym(Symbol::from(i))))
+            .collect();
+        let mut num_classes = 0;
+        loop {
+            let rule_hashes: Vec<u64> = self
+                .rules
+                .iter()
+                .map(|rule| {
+                    let rhs: Vec<u64> = rule.rhs.iter().map(|sym| labels[sym.usize()]).collect();
+                    hash_one((labels[rule.lhs.usize()], rhs))
+                })
+                .collect();
+            // Every occurrence is described by its position and the rule's hash.
+            let mut occurrences: Vec<Vec<(usize, u64)>> = vec![vec![]; self.num_syms()];
+            for (rule, &rule_hash) in self.rules.iter().zip(rule_hashes.iter()) {
+                occurrences[rule.lhs.usize()].push((0, rule_hash));
+                for (pos, sym) in rule.rhs.iter().enumerate() {
+                    occurrences[sym.usize()].push((pos + 1, rule_hash));
+                }
+            }
+            for (label, mut occurrences) in labels.iter_mut().zip(occurrences) {
+                occurrences.sort_unstable();
+                *label = hash_one((*label, occurrences));
+            }
+            let classes: HashSet<u64> = used.iter().map(|sym| labels[sym.usize()]).collect();
+            if classes.len() == num_classes {
+                let mut rule_hashes = rule_hashes;
+                rule_hashes.sort_unstable();
+                return hash_one(rule_hashes);
+            }
+            num_classes = classes.len();
+        }
+    }
+
     /// Returns a binarized grammar which is weakly equivalent to this grammar.
+    ///
+    /// If enabled with `set_merge_histories_on_dedup`, equal intermediate rules are created
+    /// only once. See `BinarizedCfg::from_context_free_merging`.
     pub fn binarize(&self) -> BinarizedCfg {
-        BinarizedCfg::from_context_free(self)
+        if self.merge_histories_on_dedup {
+            BinarizedCfg::from_context_free_merging(self)
+        } else {
+            BinarizedCfg::from_context_free(self)
+        }
+    }
+
+    /// Controls whether `binarize` deduplicates equal intermediate rules, merging
+    /// their histories. Intermediate rules stand for prefixes of right-hand sides,
+    /// so rules that share a prefix of at least two symbols share them.
+    pub fn set_merge_histories_on_dedup(&mut self, merge: bool) {
+        self.merge_histories_on_dedup = merge;
+    }
+
+    /// Indicates whether `binarize` deduplicates equal intermediate rules.
+    pub fn merge_histories_on_dedup(&self) -> bool {
+        self.merge_histories_on_dedup
+    }
+
+    /// Removes all duplicate rules, regardless of their position. The first occurrence of
+    /// every rule is kept, and the relative order of rules is preserved.
+    pub fn remove_duplicates(&mut self) {
+        let mut seen: HashSet<(Symbol, Rc<[Symbol]>)> = HashSet::new();
+        let len = self.rules.len();
+        self.rules
+            .retain(|rule| seen.insert((rule.lhs, rule.rhs.as_slice().into())));
+        if self.rules.len() != len {
+            self.revision += 1;
+        }
+    }
+
+    /// Sorts the rules of every LHS by their RHS. Each rule ends up at a position previously
+    /// occupied by a rule with the same LHS, so rules of different LHSes are not reordered
+    /// relative to each other.
+    pub fn sort_alternatives(&mut self) {
+        let mut positions: BTreeMap<Symbol, Vec<usize>> = BTreeMap::new();
+        for (idx, rule) in self.rules.iter().enumerate() {
+            positions.entry(rule.lhs).or_default().push(idx);
+        }
+        for indices in positions.values() {
+            let is_sorted = indices
+                .windows(2)
+                .all(|pair| self.rules[pair[0]].rhs <= self.rules[pair[1]].rhs);
+            if is_sorted {
+                continue;
+            }
+            self.revision += 1;
+            let mut alternatives: Vec<CfgRule> =
+                indices.iter().map(|&idx| self.rules[idx].clone()).collect();
+            alternatives.sort_by(|a, b| a.rhs.cmp(&b.rhs));
+            for (&idx, rule) in indices.iter().zip(alternatives) {
+                self.rules[idx] = rule;
+            }
+        }
+    }
+
+    /// Discards the history graph. Every rule gets a fresh `RootHistoryNode::Rule` history
+    /// followed by an `Rhs` node, as `RuleBuilder::rhs` adds, so grammars that differ only in
+    /// history become identical.
+    pub fn clear_histories(&mut self) {
+        self.history_graph = HistoryGraph::new();
+        for rule in &mut self.rules {
+            let root = self
+                .history_graph
+                .add_history_node(RootHistoryNode::Rule { lhs: rule.lhs }.into());
+            rule.history_id = self.history_graph.add_history_node(
+                HistoryNodeRhs {
+                    prev: root,
+                    rhs: rule.rhs.clone(),
+                }
+                .into(),
+            );
+        }
+    }
+
+    /// Assigns each rule a distinct label, which is usable as a key for semantic actions.
+    /// Labels are equal to rule indices at the time of the call. They are stored in rule history,
+    /// so they are preserved by grammar transformations. See `HistoryGraph::label`.
+    pub fn ensure_unique_rule_names(&mut self) {
+        for (i, rule) in self.rules.iter_mut().enumerate() {
+            rule.history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+                prev: rule.history_id,
+                node: LinkedHistoryNode::Label { id: i as u32 },
+            });
+        }
+    }
+
+    /// Checks whether the target symbol has the property after computing the RHS transitive
+    /// closure of the seed set. Stops as soon as the target gains the property.
+    pub fn closure_reaches(
+        &self,
+        seed: &SymbolBitSet,
+        target: Symbol,
+        mode: RhsPropertyMode,
+    ) -> bool {
+        let mut property = seed.clone().into_bit_vec();
+        RhsClosure::new(self).closure_reaches(&mut property, target, mode)
+    }
+
+    /// Computes the RHS transitive closure of the property in place. Returns a map from every
+    /// symbol that gains the property to the index of the rule through which it was gained.
+    pub fn rhs_closure_with_witness(
+        &self,
+        property: &mut SymbolBitSet,
+        mode: RhsPropertyMode,
+    ) -> BTreeMap<Symbol, usize> {
+        let mut bit_vec = property.clone().into_bit_vec();
+        let witness = RhsClosure::new(self).rhs_closure_with_witness(&mut bit_vec, mode);
+        for &sym in witness.keys() {
+            property.set(sym, true);
+        }
+        witness
+    }
+
+    /// Searches for a terminal string of at most `max_len` symbols that has more than one parse
+    /// tree, starting from the given symbol. Returns the first such string found.
+    ///
+    /// This is a best-effort diagnostic. Leftmost derivations are enumerated up to a bounded
+    /// number of steps, so a result of `None` does not prove that the grammar is unambiguous.
+    pub fn find_ambiguity(&self, start: Symbol, max_len: usize) -> Option<Vec<Symbol>> {
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        // Compute minimal lengths of strings derived from every symbol.
+        let mut min_len = vec![None; self.num_syms()];
+        for terminal in terminal_set.iter() {
+            min_len[terminal.usize()] = Some(1);
+        }
+        RhsClosure::new(self).rhs_closure_with_values(&mut min_len);
+
+        let max_steps = (max_len + 1) * (self.num_syms() + 1);
+        let mut derived = HashSet::new();
+        // Each entry holds a terminal prefix, the remaining symbols in reverse order, and
+        // the number of derivation steps taken so far.
+        let mut work_stack = vec![(vec![], vec![start], 0)];
+        while let Some((mut prefix, mut rest, steps)) = work_stack.pop() {
+            while let Some(&sym) = rest.last() {
+                if !terminal_set.has_sym(sym) {
+                    break;
+                }
+                prefix.push(sym);
+                rest.pop();
+            }
+            let Some(nonterminal) = rest.pop() else {
+                // Distinct leftmost derivations correspond to distinct parse trees.
+                if !derived.insert(prefix.clone()) {
+                    return Some(prefix);
+                }
+                continue;
+            };
+            if steps == max_steps {
+                continue;
+            }
+            for rule in self.rules().filter(|rule| rule.lhs == nonterminal) {
+                let mut new_rest = rest.clone();
+                new_rest.extend(rule.rhs.iter().rev());
+                let len = new_rest
+                    .iter()
+                    .map(|sym| min_len[sym.usize()])
+                    .sum::<Option<u32>>();
+                if let Some(len) = len {
+                    if prefix.len() + len as usize <= max_len {
+                        work_stack.push((prefix.clone(), new_rest, steps + 1));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Finds pairs of nonterminals that derive the same terminal strings of at most `max_len`
+    /// symbols. Pairs are ordered, with the smaller symbol first.
+    ///
+    /// This is a bounded heuristic. Reported symbols agree on short strings, but may derive
+    /// different longer strings. Nonterminals that derive no string within the bound are not
+    /// reported.
+    pub fn language_equivalent_symbols(&self, max_len: usize) -> Vec<(Symbol, Symbol)> {
+        let languages = self.bounded_languages(max_len);
+        let nonterminals: BTreeSet<Symbol> = self
+            .rules
+            .iter()
+            .map(|rule| rule.lhs)
+            .filter(|lhs| !languages[lhs.usize()].is_empty())
+            .collect();
+        let mut result = vec![];
+        for &a in &nonterminals {
+            for &b in nonterminals.range(a..).skip(1) {
+                if languages[a.usize()] == languages[b.usize()] {
+                    result.push((a, b));
+                }
+            }
+        }
+        result
+    }
+
+    /// Enumerates all distinct terminal strings of at most `max_len` symbols that are derivable
+    /// from `root`. Shorter strings come first, and strings of equal length are ordered by
+    /// their symbols.
+    pub fn enumerate(&self, root: Symbol, max_len: usize) -> impl Iterator<Item = Vec<Symbol>> {
+        let mut languages = self.bounded_languages(max_len);
+        let mut strings: Vec<_> = mem::take(&mut languages[root.usize()])
+            .into_iter()
+            .collect();
+        strings.sort_by_key(|string| string.len());
+        strings.into_iter()
+    }
+
+    /// Computes the set of terminal strings of at most `max_len` symbols derived from every
+    /// symbol.
+    fn bounded_languages(&self, max_len: usize) -> Vec<BTreeSet<Vec<Symbol>>> {
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        let mut languages = vec![BTreeSet::new(); self.num_syms()];
+        if max_len > 0 {
+            for terminal in terminal_set.iter() {
+                languages[terminal.usize()].insert(vec![terminal]);
+            }
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for rule in &self.rules {
+                let mut strings = BTreeSet::new();
+                strings.insert(vec![]);
+                for sym in &rule.rhs {
+                    let mut next = BTreeSet::new();
+                    for prefix in &strings {
+                        for suffix in &languages[sym.usize()] {
+                            if prefix.len() + suffix.len() <= max_len {
+                                next.insert([&prefix[..], &suffix[..]].concat());
+                            }
+                        }
+                    }
+                    strings = next;
+                }
+                for string in strings {
+                    changed |= languages[rule.lhs.usize()].insert(string);
+                }
+            }
+        }
+        languages
+    }
+
+    /// Moves the RHS tail of the rule at the given index, starting at position `pos`, into a rule
+    /// for a new nonterminal, which is returned. For example, `A ::= x y z w` split at position
+    /// 2 becomes `A ::= x y T` and `T ::= z w`. The new rule is added at the end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds or the tail would be empty.
+    pub fn factor_rule_tail(&mut self, rule_idx: usize, pos: usize) -> Symbol {
+        let rhs_len = self.rules[rule_idx].rhs.len();
+        assert!(
+            pos < rhs_len,
+            "cannot factor out an empty tail at position {} of a rule with {} RHS symbols",
+            pos,
+            rhs_len
+        );
+        let new_sym = self.next_sym();
+        let rule = &mut self.rules[rule_idx];
+        let tail = rule.rhs.split_off(pos);
+        rule.rhs.push(new_sym);
+        let prev = rule.history_id;
+        rule.history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+            prev,
+            node: LinkedHistoryNode::Factor {
+                pos: pos as u32,
+                tail: false,
+            },
+        });
+        let history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+            prev,
+            node: LinkedHistoryNode::Factor {
+                pos: pos as u32,
+                tail: true,
+            },
+        });
+        self.add_rule(RuleRef {
+            lhs: new_sym,
+            rhs: &tail[..],
+            history_id,
+        });
+        new_sym
+    }
+
+    /// Replaces every occurrence of the given sequence of symbols in RHSs with a new
+    /// nonterminal, which is returned together with its rule `T ::= pattern`. Occurrences are
+    /// found left to right, so of two overlapping occurrences, only the first is replaced.
+    /// The new rule is added at the end.
+    ///
+    /// Every replacement is recorded with a `FactorSubsequence` history node, both in the
+    /// rewritten rule and in the new rule. The new rule's history starts with the first
+    /// occurrence, and is merged with the others.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty.
+    pub fn factor_subsequence(&mut self, pattern: &[Symbol]) -> Symbol {
+        assert!(
+            !pattern.is_empty(),
+            "cannot factor out an empty subsequence"
+        );
+        let new_sym = self.next_sym();
+        let len = pattern.len() as u32;
+        let mut new_history: Option<HistoryId> = None;
+        for rule in &mut self.rules {
+            let mut pos = 0;
+            while pos < rule.rhs.len() {
+                if !rule.rhs[pos..].starts_with(pattern) {
+                    pos += 1;
+                    continue;
+                }
+                let factor = |inner| LinkedHistoryNode::FactorSubsequence {
+                    pos: pos as u32,
+                    len,
+                    inner,
+                };
+                let inner = self.history_graph.add_history_node(HistoryNode::Linked {
+                    prev: rule.history_id,
+                    node: factor(true),
+                });
+                new_history = Some(match new_history {
+                    Some(prev) => self.history_graph.add_history_node(HistoryNode::Linked {
+                        prev,
+                        node: LinkedHistoryNode::Merged { other: inner },
+                    }),
+                    None => inner,
+                });
+                rule.history_id = self.history_graph.add_history_node(HistoryNode::Linked {
+                    prev: rule.history_id,
+                    node: factor(false),
+                });
+                rule.rhs.splice(pos..pos + pattern.len(), Some(new_sym));
+                self.revision += 1;
+                pos += 1;
+            }
+        }
+        match new_history {
+            Some(history_id) => {
+                self.add_rule(RuleRef {
+                    lhs: new_sym,
+                    rhs: pattern,
+                    history_id,
+                });
+            }
+            None => {
+                self.rule(new_sym).rhs(pattern);
+            }
+        }
+        new_sym
+    }
+
+    /// Checks that every rule has one or two RHS symbols. Does nothing unless debug assertions
+    /// are enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics with a description of the first rule that is empty or longer than two symbols.
+    pub fn debug_assert_binary(&self) {
+        if cfg!(debug_assertions) {
+            for (idx, rule) in self.rules.iter().enumerate() {
+                assert!(
+                    matches!(rule.rhs.len(), 1 | 2),
+                    "rule {} is not binary: {:?} ::= {:?}",
+                    idx,
+                    rule.lhs,
+                    rule.rhs
+                );
+            }
+        }
+    }
+
+    /// Checks that the grammar is binarized and has no nulling rules, which Earley recognizers
+    /// require. Returns an error that describes the first rule that violates these conditions.
+    pub fn check_earley_ready(&self) -> Result<(), EarleyReadyError> {
+        for (idx, rule) in self.rules.iter().enumerate() {
+            if rule.rhs.is_empty() {
+                return Err(EarleyReadyError::Nulling {
+                    rule: idx,
+                    lhs: rule.lhs,
+                });
+            }
+            if rule.rhs.len() > 2 {
+                return Err(EarleyReadyError::NotBinary {
+                    rule: idx,
+                    lhs: rule.lhs,
+                    rhs: rule.rhs.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Inlines nonterminals that have exactly one rule and occur exactly once on right-hand
+    /// sides of rules. Roots and directly recursive nonterminals are never inlined. The rule that
+    /// receives the inlined RHS gets an `Rhs` history node for its new RHS.
+    ///
+    /// A `Cfg` does not know its roots, so they are given. A root may occur once on a RHS,
+    /// and would then lose its rule if it were inlined.
+    pub fn inline_single_use(&mut self, roots: &[Symbol]) {
+        while let Some((def_idx, use_idx, pos)) = self.find_single_use(roots) {
+            let definition = self.rules.remove(def_idx);
+            let use_idx = if use_idx > def_idx {
+                use_idx - 1
+            } else {
+                use_idx
+            };
+            let mut rhs = self.rules[use_idx].rhs.clone();
+            rhs.splice(pos..=pos, definition.rhs.iter().cloned());
+            self.replace_rhs(use_idx, rhs);
+        }
+    }
+
+    /// Replaces the RHS of a rule, and records the new RHS in its history.
+    fn replace_rhs(&mut self, rule_idx: usize, rhs: Vec<Symbol>) {
+        let rule = &mut self.rules[rule_idx];
+        rule.history_id = self.history_graph.add_history_node(
+            HistoryNodeRhs {
+                prev: rule.history_id,
+                rhs: rhs.clone(),
+            }
+            .into(),
+        );
+        rule.rhs = rhs;
+        self.revision += 1;
+    }
+
+    /// Flattens chains of nonterminals that have a single production each, such as
+    /// `A ::= B; B ::= C; C ::= x y`, into `A ::= x y`. The outermost rule keeps its LHS,
+    /// and gets an `Rhs` history node for its new RHS. Rules of inner nonterminals are removed
+    /// once no rule refers to them, unless they are roots. Recursive chains are left as is.
+    pub fn flatten_trivial_chains(&mut self, roots: &[Symbol]) {
+        let mut definitions = vec![vec![]; self.num_syms()];
+        for (rule_idx, rule) in self.rules.iter().enumerate() {
+            definitions[rule.lhs.usize()].push(rule_idx);
+        }
+        let single = |sym: Symbol| match definitions[sym.usize()][..] {
+            [rule_idx] => Some(rule_idx),
+            _ => None,
+        };
+        let mut inlined = BTreeSet::new();
+        for rule_idx in 0..self.rules.len() {
+            if single(self.rules[rule_idx].lhs).is_none() {
+                continue;
+            }
+            let mut visited = BTreeSet::new();
+            visited.insert(self.rules[rule_idx].lhs);
+            let mut rhs = &self.rules[rule_idx].rhs;
+            while let [inner] = rhs[..] {
+                let inner_rule = match single(inner) {
+                    Some(inner_rule) if visited.insert(inner) => inner_rule,
+                    _ => break,
+                };
+                let inner_rhs = &self.rules[inner_rule].rhs;
+                if inner_rhs.iter().any(|sym| visited.contains(sym)) {
+                    break;
+                }
+                rhs = inner_rhs;
+                inlined.insert(inner);
+            }
+            if *rhs != self.rules[rule_idx].rhs {
+                self.replace_rhs(rule_idx, rhs.clone());
+            }
+        }
+        inlined.retain(|sym| !roots.contains(sym));
+        loop {
+            let used: BTreeSet<Symbol> = self
+                .rules
+                .iter()
+                .flat_map(|rule| rule.rhs.iter().cloned())
+                .collect();
+            let len = self.rules.len();
+            self.rules
+                .retain(|rule| !inlined.contains(&rule.lhs) || used.contains(&rule.lhs));
+            if self.rules.len() == len {
+                break;
+            }
+            self.revision += 1;
+        }
+    }
+
+    /// Finds a nonterminal to inline. Returns the index of its only rule, and the rule index
+    /// and position of its only occurrence.
+    fn find_single_use(&self, roots: &[Symbol]) -> Option<(usize, usize, usize)> {
+        let mut definitions = vec![vec![]; self.num_syms()];
+        let mut occurrences = vec![vec![]; self.num_syms()];
+        for (rule_idx, rule) in self.rules.iter().enumerate() {
+            definitions[rule.lhs.usize()].push(rule_idx);
+            for (pos, &sym) in rule.rhs.iter().enumerate() {
+                occurrences[sym.usize()].push((rule_idx, pos));
+            }
+        }
+        (0..self.num_syms()).find_map(|id| {
+            let sym = Symbol::from(id);
+            match (&definitions[id][..], &occurrences[id][..]) {
+                (&[def_idx], &[(use_idx, pos)])
+                    if !roots.contains(&sym) && !self.rules[def_idx].rhs.contains(&sym) =>
+                {
+                    Some((def_idx, use_idx, pos))
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns every LR(0) item of the grammar as a triple of the rule's index, the dot
+    /// position and the rule. Dot positions range from 0 to the RHS length, inclusive.
+    pub fn dotted_items(&self) -> impl Iterator<Item = (usize, usize, RuleRef<'_>)> {
+        self.indexed_rules()
+            .flat_map(|(rule_idx, rule)| (0..=rule.rhs.len()).map(move |dot| (rule_idx, dot, rule)))
+    }
+
+    /// Checks whether the rule's RHS is nonempty and consists only of terminals.
+    ///
+    /// Computes the set of terminals on every call. To classify all rules, use `rhs_kinds`.
+    pub fn rule_is_purely_terminal<R: AsRuleRef>(&self, rule: R) -> bool {
+        self.rhs_kind_of(rule) == RhsKind::PurelyTerminal
+    }
+
+    /// Checks whether the rule's RHS is nonempty and consists only of nonterminals.
+    ///
+    /// Computes the set of terminals on every call. To classify all rules, use `rhs_kinds`.
+    pub fn rule_is_purely_nonterminal<R: AsRuleRef>(&self, rule: R) -> bool {
+        self.rhs_kind_of(rule) == RhsKind::PurelyNonterminal
+    }
+
+    /// Checks whether the rule's RHS contains both terminals and nonterminals.
+    ///
+    /// Computes the set of terminals on every call. To classify all rules, use `rhs_kinds`.
+    pub fn rule_is_mixed<R: AsRuleRef>(&self, rule: R) -> bool {
+        self.rhs_kind_of(rule) == RhsKind::Mixed
+    }
+
+    /// Classifies the RHS of every rule, in the order of rule iteration. The set of
+    /// terminals is computed once.
+    pub fn rhs_kinds(&self) -> Vec<RhsKind> {
+        let terminal_set = SymbolBitSet::terminal_set(self);
+        self.rules
+            .iter()
+            .map(|rule| RhsKind::new(&terminal_set, &rule.rhs[..]))
+            .collect()
+    }
+
+    fn rhs_kind_of<R: AsRuleRef>(&self, rule: R) -> RhsKind {
+        RhsKind::new(&SymbolBitSet::terminal_set(self), rule.as_rule_ref().rhs)
+    }
+}
+
+impl RhsKind {
+    /// Classifies a RHS. Terminals are symbols in the given set.
+    pub fn new(terminal_set: &SymbolBitSet, rhs: &[Symbol]) -> Self {
+        let has_terminal = rhs.iter().any(|&sym| terminal_set.has_sym(sym));
+        let has_nonterminal = rhs.iter().any(|&sym| !terminal_set.has_sym(sym));
+        match (has_terminal, has_nonterminal) {
+            (false, false) => RhsKind::Empty,
+            (true, false) => RhsKind::PurelyTerminal,
+            (false, true) => RhsKind::PurelyNonterminal,
+            (true, true) => RhsKind::Mixed,
+        }
     }
 }
 
+fn hash_one<T: Hash>(value: T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
 impl RuleContainer for Cfg {
     fn rules<'a>(&'a self) -> impl Iterator<Item = RuleRef<'a>>
     where
@@ -83,10 +1292,15 @@ impl RuleContainer for Cfg {
     where
         F: FnMut(RuleRef) -> bool,
     {
+        let len = self.rules.len();
         self.rules.retain(|rule| f(rule.as_rule_ref()));
+        if self.rules.len() != len {
+            self.revision += 1;
+        }
     }
 
     fn add_rule(&mut self, rule_ref: RuleRef) {
+        self.revision += 1;
         self.rules.push(CfgRule {
             lhs: rule_ref.lhs,
             rhs: rule_ref.rhs.to_vec(),
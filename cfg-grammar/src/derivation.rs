@@ -1,6 +1,9 @@
+//! Derivation matrices, indexed by pairs of symbols.
+
 use bit_matrix::BitMatrix;
 
-use cfg_grammar::RuleContainer;
+use crate::symbol::set::SymbolBitSet;
+use crate::RuleContainer;
 
 /// Returns the direct derivation matrix.
 pub fn direct_derivation_matrix<'a, G>(grammar: &'a G) -> BitMatrix
@@ -19,7 +22,8 @@ where
     derivation
 }
 
-/// Returns the derivation matrix.
+/// Returns the derivation matrix. Symbol A is related to symbol B if A derives a string
+/// that contains B in zero or more steps.
 pub fn reachability_matrix<'a, G>(grammar: &'a G) -> BitMatrix
 where
     G: RuleContainer,
@@ -50,3 +54,36 @@ where
     unit_derivation.transitive_closure();
     unit_derivation
 }
+
+/// Returns the unit derivation matrix, where rules are treated as unit rules once their
+/// nullable RHS symbols are removed. Symbol A is related to symbol B if A derives B with
+/// the help of nullable symbols.
+pub fn nullable_unit_derivation_matrix<G>(grammar: &G) -> BitMatrix
+where
+    G: RuleContainer,
+{
+    let num_syms = grammar.num_syms();
+    let nullable = SymbolBitSet::nullable_set(grammar);
+    let mut unit_derivation = BitMatrix::new(num_syms, num_syms);
+
+    for rule in grammar.rules() {
+        // As in `unit_derivation_matrix`, the rule `A ::= A` is not a cycle.
+        if rule.rhs.len() == 1 && rule.lhs == rule.rhs[0] {
+            continue;
+        }
+        let num_non_nullable = rule
+            .rhs
+            .iter()
+            .filter(|&&sym| !nullable.has_sym(sym))
+            .count();
+        for &sym in rule.rhs {
+            // All other RHS symbols must be nullable.
+            if num_non_nullable == 0 || (num_non_nullable == 1 && !nullable.has_sym(sym)) {
+                unit_derivation.set(rule.lhs.into(), sym.into(), true);
+            }
+        }
+    }
+
+    unit_derivation.transitive_closure();
+    unit_derivation
+}
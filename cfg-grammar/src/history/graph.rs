@@ -1,10 +1,40 @@
-use super::node::{HistoryId, HistoryNode, RootHistoryNode};
+use std::iter;
+
+use cfg_symbol::Symbol;
+
+use super::node::{HistoryId, HistoryNode, LinkedHistoryNode, RootHistoryNode};
 
 #[derive(Clone)]
 pub struct HistoryGraph {
     nodes: Vec<HistoryNode>,
 }
 
+/// The error returned when a history graph or a rule refers to a missing history node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HistoryError {
+    /// A linked node refers to a node that does not come before it.
+    ForwardReference {
+        /// The index of the linked node.
+        node: usize,
+        /// The ID it refers to.
+        prev: HistoryId,
+    },
+    /// A merged node refers to another history that does not come before it.
+    MergedForwardReference {
+        /// The index of the merged node.
+        node: usize,
+        /// The ID it refers to.
+        other: HistoryId,
+    },
+    /// A rule carries a history ID that does not exist.
+    RuleOutOfRange {
+        /// The index of the rule.
+        rule: usize,
+        /// The rule's history ID.
+        history_id: HistoryId,
+    },
+}
+
 impl HistoryGraph {
     pub fn new() -> Self {
         Self {
@@ -24,6 +54,93 @@ impl HistoryGraph {
         self.push(node);
         result
     }
+
+    /// Checks that every linked node refers to an earlier node, and that every merged node
+    /// merges an earlier node, which is what processing history in order relies on.
+    pub fn validate(&self) -> Result<(), HistoryError> {
+        for (id, node) in self.nodes.iter().enumerate() {
+            if let HistoryNode::Linked { prev, ref node } = *node {
+                if prev.get() >= id {
+                    return Err(HistoryError::ForwardReference { node: id, prev });
+                }
+                if let LinkedHistoryNode::Merged { other } = *node {
+                    if other.get() >= id {
+                        return Err(HistoryError::MergedForwardReference { node: id, other });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the nodes in the given node's chain of history, starting with that node and
+    /// ending with its root.
+    pub fn chain(&self, history_id: HistoryId) -> impl Iterator<Item = &HistoryNode> + '_ {
+        iter::successors(
+            Some(&self.nodes[history_id.get()]),
+            move |node| match **node {
+                HistoryNode::Linked { prev, .. } => Some(&self.nodes[prev.get()]),
+                HistoryNode::Root(_) => None,
+            },
+        )
+    }
+
+    /// Returns the label closest to the given node in its chain of history, if any.
+    pub fn label(&self, mut history_id: HistoryId) -> Option<u32> {
+        while let HistoryNode::Linked { prev, ref node } = self.nodes[history_id.get()] {
+            if let LinkedHistoryNode::Label { id } = *node {
+                return Some(id);
+            }
+            history_id = prev;
+        }
+        None
+    }
+
+    /// Returns the looseness closest to the given node in its chain of history, if any.
+    pub fn precedence(&self, mut history_id: HistoryId) -> Option<u32> {
+        while let HistoryNode::Linked { prev, ref node } = self.nodes[history_id.get()] {
+            if let LinkedHistoryNode::AssignPrecedence { looseness } = *node {
+                return Some(looseness);
+            }
+            history_id = prev;
+        }
+        None
+    }
+
+    /// Returns the sequence rewrite closest to the given node in its chain of history, if any,
+    /// as its `top` flag, repeated symbol and separator.
+    pub fn sequence(&self, mut history_id: HistoryId) -> Option<(bool, Symbol, Option<Symbol>)> {
+        while let HistoryNode::Linked { prev, ref node } = self.nodes[history_id.get()] {
+            if let LinkedHistoryNode::RewriteSequence { top, rhs, sep, .. } = *node {
+                return Some((top, rhs, sep));
+            }
+            history_id = prev;
+        }
+        None
+    }
+
+    /// Returns the repetition bounds of the sequence rewrite closest to the given node in its
+    /// chain of history, if any, as the minimum and the optional maximum.
+    pub fn sequence_bounds(&self, mut history_id: HistoryId) -> Option<(u32, Option<u32>)> {
+        while let HistoryNode::Linked { prev, ref node } = self.nodes[history_id.get()] {
+            if let LinkedHistoryNode::RewriteSequence { start, end, .. } = *node {
+                return Some((start, end));
+            }
+            history_id = prev;
+        }
+        None
+    }
+
+    /// Returns the weight closest to the given node in its chain of history, if any.
+    pub fn weight(&self, mut history_id: HistoryId) -> Option<f64> {
+        while let HistoryNode::Linked { prev, ref node } = self.nodes[history_id.get()] {
+            if let LinkedHistoryNode::Weight { weight } = *node {
+                return Some(weight);
+            }
+            history_id = prev;
+        }
+        None
+    }
 }
 
 impl ::std::ops::Deref for HistoryGraph {
@@ -36,6 +36,8 @@ pub enum LinkedHistoryNode {
         top: bool,
         rhs: Symbol,
         sep: Option<Symbol>,
+        start: u32,
+        end: Option<u32>,
     },
     Weight {
         weight: f64,
@@ -43,6 +45,21 @@ pub enum LinkedHistoryNode {
     Distances {
         events: Vec<u32>,
     },
+    Label {
+        id: u32,
+    },
+    Factor {
+        pos: u32,
+        tail: bool,
+    },
+    FactorSubsequence {
+        pos: u32,
+        len: u32,
+        inner: bool,
+    },
+    Merged {
+        other: HistoryId,
+    },
 }
 
 #[derive(Clone, Copy)]
@@ -95,6 +112,8 @@ pub struct HistoryNodeRewriteSequence {
     pub top: bool,
     pub rhs: Symbol,
     pub sep: Option<Symbol>,
+    pub start: u32,
+    pub end: Option<u32>,
 }
 
 impl From<HistoryNodeRhs> for HistoryNode {
@@ -158,6 +177,8 @@ impl From<HistoryNodeRewriteSequence> for HistoryNode {
                 top: value.top,
                 rhs: value.rhs,
                 sep: value.sep,
+                start: value.start,
+                end: value.end,
             },
         }
     }
@@ -15,11 +15,16 @@
 
 pub mod binarized_cfg;
 pub mod cfg;
+pub mod derivation;
 pub mod history;
 pub mod precedenced_rule;
+pub mod prefix_trie;
+pub mod railroad;
 pub mod rhs_closure;
 pub mod rule;
 pub mod rule_container;
+#[cfg(feature = "serialize")]
+mod serialize;
 pub mod symbol;
 
 pub use crate::binarized_cfg::BinarizedCfg;
@@ -21,6 +21,24 @@ pub enum Associativity {
     Group,
 }
 
+/// The error returned when precedenced rules do not form a proper precedence ladder.
+/// See `Cfg::validate_precedence`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PrecedenceError {
+    /// Rules with this LHS were assigned different looseness levels.
+    MixedLooseness {
+        /// The left-hand side of the rules.
+        lhs: Symbol,
+    },
+    /// The symbol of a looser level does not derive the symbol of the next tighter level.
+    MissingTighterLevel {
+        /// The symbol of the looser level.
+        lhs: Symbol,
+        /// The looseness of the level.
+        looseness: u32,
+    },
+}
+
 /// The default associativity.
 pub const DEFAULT_ASSOC: Associativity = Left;
 
@@ -0,0 +1,86 @@
+//! Tries of RHS prefixes.
+
+use std::collections::BTreeMap;
+
+use crate::local_prelude::*;
+
+/// A trie over symbol sequences, such as the alternatives of a nonterminal.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PrefixTrie {
+    nodes: Vec<PrefixTrieNode>,
+}
+
+/// A node of a prefix trie.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PrefixTrieNode {
+    /// Children of this node, keyed by the next symbol.
+    pub children: BTreeMap<Symbol, usize>,
+    /// The index of the rule whose sequence ends at this node, if any.
+    pub rule: Option<usize>,
+}
+
+impl Default for PrefixTrie {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrefixTrie {
+    /// Creates a trie that consists of only the root node.
+    pub fn new() -> Self {
+        PrefixTrie {
+            nodes: vec![PrefixTrieNode::default()],
+        }
+    }
+
+    /// Inserts a sequence that ends with the given rule. If the same sequence was inserted
+    /// before, the earlier rule index is kept.
+    pub fn insert(&mut self, symbols: &[Symbol], rule: usize) {
+        let mut node = 0;
+        for &sym in symbols {
+            let next_id = self.nodes.len();
+            node = match self.nodes[node].children.get(&sym) {
+                Some(&child) => child,
+                None => {
+                    self.nodes[node].children.insert(sym, next_id);
+                    self.nodes.push(PrefixTrieNode::default());
+                    next_id
+                }
+            };
+        }
+        self.nodes[node].rule.get_or_insert(rule);
+    }
+
+    /// Returns the root node, which corresponds to the empty prefix.
+    pub fn root(&self) -> &PrefixTrieNode {
+        &self.nodes[0]
+    }
+
+    /// Returns the node with the given id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is out of bounds.
+    pub fn node(&self, id: usize) -> &PrefixTrieNode {
+        &self.nodes[id]
+    }
+
+    /// Returns the node reached by following the given prefix, if any.
+    pub fn find(&self, prefix: &[Symbol]) -> Option<&PrefixTrieNode> {
+        let mut node = self.root();
+        for sym in prefix {
+            node = self.node(*node.children.get(sym)?);
+        }
+        Some(node)
+    }
+
+    /// Returns the number of nodes, including the root.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Determines whether no sequence was inserted.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.len() == 1 && self.nodes[0].rule.is_none()
+    }
+}
@@ -0,0 +1,28 @@
+//! A data model of railroad diagrams.
+
+use crate::local_prelude::*;
+
+/// A node of a railroad diagram.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RailNode {
+    /// A terminal symbol.
+    Terminal(Symbol),
+    /// A reference to a nonterminal, which has a diagram of its own.
+    Nonterminal(Symbol),
+    /// Nodes passed one after another.
+    Sequence(Vec<RailNode>),
+    /// A choice between alternatives.
+    Choice(Vec<RailNode>),
+    /// A repetition of an item, optionally with a separator between items. The loop can be
+    /// skipped when `min` is 0.
+    Repeat {
+        /// The repeated node.
+        item: Box<RailNode>,
+        /// The separator, if any.
+        separator: Option<Box<RailNode>>,
+        /// The minimum number of repetitions.
+        min: u32,
+        /// The maximum number of repetitions, or `None` if unbounded.
+        max: Option<u32>,
+    },
+}
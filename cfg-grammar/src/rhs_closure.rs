@@ -1,5 +1,7 @@
 use std::cmp;
 use std::cmp::Ordering::{Greater, Less};
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
 
 use bit_vec::BitVec;
 
@@ -8,14 +10,29 @@ use crate::rule::RuleRef;
 
 /// Rhs closure. In some sense, it is a reverse of breadth
 /// first search (reverse BFS).
+///
+/// The LHS of a rule with an empty RHS gains every property in the `All` mode, because all
+/// of its RHS symbols trivially have it. For values, such a LHS gets a value of zero.
 pub struct RhsClosure<'a> {
     inverse_derivation: Vec<Derivation<'a>>,
+    /// The LHS and index of every rule with an empty RHS.
+    empty_rules: Vec<(Symbol, usize)>,
     work_stack: Vec<Symbol>,
 }
 
+/// Determines whether a LHS gains a property when all or any of its RHS symbols have it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RhsPropertyMode {
+    /// All RHS symbols must have the property.
+    All,
+    /// At least one RHS symbol must have the property.
+    Any,
+}
+
 struct Derivation<'a> {
     sym: Symbol,
     rule_ref: RuleRef<'a>,
+    rule_idx: usize,
 }
 
 impl<'a> Ord for Derivation<'a> {
@@ -49,10 +66,15 @@ impl<'a> RhsClosure<'a> {
         G: RuleContainer,
     {
         let mut inverse_derivation = Vec::with_capacity(2 * grammar.rules().size_hint().0);
-        for rule in grammar.rules() {
+        let mut empty_rules = vec![];
+        for (rule_idx, rule) in grammar.indexed_rules() {
+            if rule.rhs.is_empty() {
+                empty_rules.push((rule.lhs, rule_idx));
+            }
             inverse_derivation.extend(rule.rhs.iter().map(|&sym| Derivation {
                 sym,
                 rule_ref: rule,
+                rule_idx,
             }));
         }
 
@@ -60,62 +82,116 @@ impl<'a> RhsClosure<'a> {
 
         RhsClosure {
             inverse_derivation: inverse_derivation,
+            empty_rules,
             work_stack: vec![],
         }
     }
 
     // Calculates the RHS transitive closure.
     pub fn rhs_closure(&mut self, property: &mut BitVec) {
-        for (sym_id, sym_has_property) in property.iter().enumerate() {
-            if sym_has_property {
-                self.work_stack.push(Symbol::from(sym_id));
-            }
-        }
-
-        let inverse_derivation = &self.inverse_derivation[..];
-        while let Some(work_sym) = self.work_stack.pop() {
-            for derivation in find(inverse_derivation, work_sym) {
-                if !property[derivation.rule_ref.lhs.usize()]
-                    && derivation
-                        .rule_ref
-                        .rhs
-                        .iter()
-                        .all(|sym| property[sym.usize()])
-                {
-                    property.set(derivation.rule_ref.lhs.usize(), true);
-                    self.work_stack.push(derivation.rule_ref.lhs);
-                }
-            }
-        }
+        self.closure(property, RhsPropertyMode::All, |_, _| {
+            ControlFlow::Continue(())
+        });
     }
 
     // Calculates the RHS transitive closure.
     pub fn rhs_closure_for_any(&mut self, property: &mut BitVec) {
+        self.closure(property, RhsPropertyMode::Any, |_, _| {
+            ControlFlow::Continue(())
+        });
+    }
+
+    /// Calculates the RHS transitive closure until the target symbol gains the property.
+    /// Returns whether it does.
+    pub fn closure_reaches(
+        &mut self,
+        property: &mut BitVec,
+        target: Symbol,
+        mode: RhsPropertyMode,
+    ) -> bool {
+        if property[target.usize()] {
+            return true;
+        }
+        self.closure(property, mode, |lhs, _| {
+            if lhs == target {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Calculates the RHS transitive closure. Returns a map from every symbol that gains
+    /// the property to the index of the rule through which it was gained.
+    pub fn rhs_closure_with_witness(
+        &mut self,
+        property: &mut BitVec,
+        mode: RhsPropertyMode,
+    ) -> BTreeMap<Symbol, usize> {
+        let mut witness = BTreeMap::new();
+        self.closure(property, mode, |lhs, rule_idx| {
+            witness.insert(lhs, rule_idx);
+            ControlFlow::Continue(())
+        });
+        witness
+    }
+
+    /// Calculates the RHS transitive closure in the given mode. Calls `on_gain` with every
+    /// symbol that gains the property and the index of the rule through which it was gained,
+    /// and stops early if it returns `Break`. Returns whether it stopped early.
+    fn closure(
+        &mut self,
+        property: &mut BitVec,
+        mode: RhsPropertyMode,
+        mut on_gain: impl FnMut(Symbol, usize) -> ControlFlow<()>,
+    ) -> bool {
+        self.work_stack.clear();
         for (sym_id, sym_has_property) in property.iter().enumerate() {
             if sym_has_property {
                 self.work_stack.push(Symbol::from(sym_id));
             }
         }
+        if mode == RhsPropertyMode::All {
+            for &(lhs, rule_idx) in &self.empty_rules {
+                if !property[lhs.usize()] {
+                    property.set(lhs.usize(), true);
+                    self.work_stack.push(lhs);
+                    if on_gain(lhs, rule_idx).is_break() {
+                        self.work_stack.clear();
+                        return true;
+                    }
+                }
+            }
+        }
 
         let inverse_derivation = &self.inverse_derivation[..];
         while let Some(work_sym) = self.work_stack.pop() {
             for derivation in find(inverse_derivation, work_sym) {
-                if !property[derivation.rule_ref.lhs.usize()]
-                    && derivation
-                        .rule_ref
-                        .rhs
-                        .iter()
-                        .any(|sym| property[sym.usize()])
-                {
-                    property.set(derivation.rule_ref.lhs.usize(), true);
-                    self.work_stack.push(derivation.rule_ref.lhs);
+                let lhs = derivation.rule_ref.lhs;
+                let mut rhs = derivation.rule_ref.rhs.iter();
+                let gains_property = match mode {
+                    RhsPropertyMode::All => rhs.all(|sym| property[sym.usize()]),
+                    RhsPropertyMode::Any => rhs.any(|sym| property[sym.usize()]),
+                };
+                if !property[lhs.usize()] && gains_property {
+                    property.set(lhs.usize(), true);
+                    self.work_stack.push(lhs);
+                    if on_gain(lhs, derivation.rule_idx).is_break() {
+                        self.work_stack.clear();
+                        return true;
+                    }
                 }
             }
         }
+        false
     }
 
     // Calculates the RHS transitive closure.
     pub fn rhs_closure_with_values(&mut self, value: &mut Vec<Option<u32>>) {
+        for &(lhs, _) in &self.empty_rules {
+            value[lhs.usize()] = Some(0);
+        }
+        self.work_stack.clear();
         for (sym_id, maybe_sym_value) in value.iter().enumerate() {
             if maybe_sym_value.is_some() {
                 self.work_stack.push(Symbol::from(sym_id));
@@ -43,6 +43,13 @@ where
         self
     }
 
+    /// Starts building a new rule with the LHS of the given name. The symbol is created if
+    /// no symbol has that name.
+    pub fn rule_str(mut self, lhs: &str) -> Self {
+        let lhs = self.rules.sym_source_mut().intern_name(lhs);
+        self.rule(lhs)
+    }
+
     /// Assigns the rule history, which is used on the next call to `rhs`, or overwritten by a call
     /// to `rhs_with_history`.
     pub fn history(mut self, history: HistoryId) -> Self {
@@ -51,7 +58,7 @@ where
     }
 
     /// Adds a rule alternative to the grammar. If history wasn't provided, the rule has the
-    /// `Default` history.
+    /// `Default` history. Every call adds a distinct rule.
     pub fn rhs<S>(mut self, syms: S) -> Self
     where
         S: AsRef<[Symbol]>,
@@ -83,6 +90,17 @@ where
         self.rhs_with_history(syms, new_history)
     }
 
+    /// Adds a rule alternative with symbols of the given names. Symbols are created if
+    /// no symbol has a name.
+    pub fn rhs_str(mut self, rhs: &[&str]) -> Self {
+        let sym_source = self.rules.sym_source_mut();
+        let rhs: Vec<Symbol> = rhs
+            .iter()
+            .map(|name| sym_source.intern_name(name))
+            .collect();
+        self.rhs(rhs)
+    }
+
     /// Adds a rule alternative with the given RHS and history to the grammar.
     pub fn rhs_with_history<Sr>(mut self, syms: Sr, history_id: HistoryId) -> Self
     where
@@ -1,3 +1,7 @@
+use std::collections::BTreeMap;
+
+use crate::history::graph::HistoryError;
+use crate::history::node::{BinarizedRhsSubset, LinkedHistoryNode, RootHistoryNode};
 use crate::history::{HistoryGraph, HistoryId, HistoryNode};
 use crate::local_prelude::*;
 use crate::precedenced_rule::PrecedencedRuleBuilder;
@@ -47,6 +51,171 @@ pub trait RuleContainer: Sized {
         PrecedencedRuleBuilder::new(self, lhs)
     }
 
+    /// Adds rules for an optional symbol, `X?`. Returns a new symbol that derives either
+    /// the empty string or `rhs`.
+    fn optional(&mut self, rhs: Symbol) -> Symbol {
+        let opt = self.next_sym();
+        self.rule(opt).rhs([]).rhs([rhs]);
+        opt
+    }
+
+    /// Names unnamed symbols after the symbols they were derived from, based on rule history.
+    /// Symbols introduced by sequence rewriting are named `<item>_list`, where `<item>` is
+    /// the name of the repeated symbol. Symbols introduced by binarization are named
+    /// `<lhs>.<depth>`, where `<lhs>` is the name of the original rule's LHS.
+    fn auto_label(&mut self) {
+        let mut lists = vec![];
+        // Binarized rules, grouped by the history of their original rule.
+        let mut binarized: BTreeMap<HistoryId, Vec<(Symbol, u32)>> = BTreeMap::new();
+        for rule in self.rules() {
+            let mut history_id = rule.history_id;
+            while let HistoryNode::Linked { prev, ref node } =
+                self.history_graph()[history_id.get()]
+            {
+                match *node {
+                    LinkedHistoryNode::Binarize { depth } => {
+                        binarized.entry(prev).or_default().push((rule.lhs, depth));
+                        if depth != 0 {
+                            // Earlier history describes the original LHS.
+                            break;
+                        }
+                    }
+                    LinkedHistoryNode::RewriteSequence {
+                        top: false, rhs, ..
+                    } => {
+                        lists.push((rule.lhs, rhs));
+                    }
+                    _ => {}
+                }
+                history_id = prev;
+            }
+        }
+        let sym_source = self.sym_source_mut();
+        for (lhs, item) in lists {
+            if sym_source.name_of(lhs).is_none() {
+                if let Some(name) = sym_source
+                    .name_of(item)
+                    .map(|name| format!("{}_list", name))
+                {
+                    sym_source.set_name(lhs, &name);
+                }
+            }
+        }
+        for chain in binarized.values() {
+            let top_name = chain
+                .iter()
+                .find(|&&(_, depth)| depth == 0)
+                .and_then(|&(lhs, _)| sym_source.name_of(lhs))
+                .map(String::from);
+            let top_name = match top_name {
+                Some(name) => name,
+                None => continue,
+            };
+            for &(lhs, depth) in chain {
+                if depth != 0 && sym_source.name_of(lhs).is_none() {
+                    sym_source.set_name(lhs, &format!("{}.{}", top_name, depth));
+                }
+            }
+        }
+    }
+
+    /// Checks that the history graph is valid and that every rule's history ID refers to
+    /// a node in it. See `HistoryGraph::validate`.
+    fn validate_history(&self) -> Result<(), HistoryError> {
+        self.history_graph().validate()?;
+        for (idx, rule) in self.indexed_rules() {
+            if rule.history_id.get() >= self.history_graph().len() {
+                return Err(HistoryError::RuleOutOfRange {
+                    rule: idx,
+                    history_id: rule.history_id,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Describes the chain of history of the rule at the given index, one node per line,
+    /// starting with the root. Symbols are written by name, or as `g<id>` when unnamed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    fn debug_history(&self, rule_idx: usize) -> String {
+        let rule = self
+            .rules()
+            .nth(rule_idx)
+            .expect("rule index out of bounds");
+        let sym_source = self.sym_source();
+        let name = |sym: Symbol| match sym_source.name_of(sym) {
+            Some(name) => name.to_string(),
+            None => format!("g{}", sym.usize()),
+        };
+        let names = |syms: &[Symbol]| syms.iter().map(|&sym| name(sym)).collect::<Vec<_>>();
+        let mut lines: Vec<String> = self
+            .history_graph()
+            .chain(rule.history_id)
+            .map(|node| match *node {
+                HistoryNode::Root(RootHistoryNode::NoOp) => "no-op".to_string(),
+                HistoryNode::Root(RootHistoryNode::Rule { lhs }) => {
+                    format!("rule for {}", name(lhs))
+                }
+                HistoryNode::Root(RootHistoryNode::Origin { origin }) => {
+                    format!("origin {}", origin)
+                }
+                HistoryNode::Linked { ref node, .. } => match *node {
+                    LinkedHistoryNode::Rhs { ref rhs } => {
+                        format!("rhs [{}]", names(rhs).join(" "))
+                    }
+                    LinkedHistoryNode::Binarize { depth } => format!("binarize at depth {}", depth),
+                    LinkedHistoryNode::EliminateNulling {
+                        rhs0, rhs1, which, ..
+                    } => {
+                        let rhs = names(&[rhs0].into_iter().chain(rhs1).collect::<Vec<_>>());
+                        let which = match which {
+                            BinarizedRhsSubset::Left => "left",
+                            BinarizedRhsSubset::Right => "right",
+                            BinarizedRhsSubset::All => "all",
+                        };
+                        format!("eliminate nulling {} of [{}]", which, rhs.join(" "))
+                    }
+                    LinkedHistoryNode::AssignPrecedence { looseness } => {
+                        format!("precedence {}", looseness)
+                    }
+                    LinkedHistoryNode::RewriteSequence { top, rhs, sep, .. } => {
+                        let part = if top { "top" } else { "bottom" };
+                        match sep {
+                            Some(sep) => format!(
+                                "rewrite sequence {} of {} separated by {}",
+                                part,
+                                name(rhs),
+                                name(sep)
+                            ),
+                            None => format!("rewrite sequence {} of {}", part, name(rhs)),
+                        }
+                    }
+                    LinkedHistoryNode::Weight { weight } => format!("weight {}", weight),
+                    LinkedHistoryNode::Distances { ref events } => {
+                        format!("distances {:?}", events)
+                    }
+                    LinkedHistoryNode::Label { id } => format!("label {}", id),
+                    LinkedHistoryNode::Factor { pos, tail } => {
+                        let part = if tail { "tail" } else { "head" };
+                        format!("factor {} at {}", part, pos)
+                    }
+                    LinkedHistoryNode::FactorSubsequence { pos, len, inner } => {
+                        let part = if inner { "inner" } else { "outer" };
+                        format!("factor subsequence {} of length {} at {}", part, len, pos)
+                    }
+                    LinkedHistoryNode::Merged { other } => {
+                        format!("merged with history {}", other.get())
+                    }
+                },
+            })
+            .collect();
+        lines.reverse();
+        lines.join("\n")
+    }
+
     fn history_graph(&self) -> &HistoryGraph;
 
     fn add_history_node(&mut self, node: HistoryNode) -> HistoryId;
@@ -56,6 +225,16 @@ pub trait RuleContainer: Sized {
         Self: 'a;
     // fn rules<'a>(&'a self) -> Self::Rules<'a>;
 
+    /// Returns rules paired with their indices. A rule's index is its position in the order
+    /// of `rules`. Methods that take or return rule indices, such as `debug_history`, use
+    /// this numbering. Indices are only stable until the grammar is modified.
+    fn indexed_rules<'a>(&'a self) -> impl Iterator<Item = (usize, RuleRef<'a>)>
+    where
+        Self: 'a,
+    {
+        self.rules().enumerate()
+    }
+
     /// Reverses the grammar.
     fn reverse(&self) -> Self
     where
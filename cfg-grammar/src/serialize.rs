@@ -0,0 +1,277 @@
+//! Serialization of grammars into JSON.
+//!
+//! Every history node is stored as its kind, followed by symbols and integers
+//! that describe it. For example, a `Binarize` node has a single value for its depth.
+
+use miniserde::{json, Deserialize, Error, Result, Serialize};
+
+use crate::history::node::{BinarizedRhsSubset, LinkedHistoryNode, RootHistoryNode};
+use crate::local_prelude::*;
+use crate::rule::RuleRef;
+use crate::Cfg;
+
+#[derive(Serialize, Deserialize)]
+struct CfgRepr {
+    num_syms: usize,
+    names: Vec<Option<String>>,
+    rules: Vec<CfgRuleRepr>,
+    history: Vec<HistoryNodeRepr>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CfgRuleRepr {
+    lhs: Symbol,
+    rhs: Vec<Symbol>,
+    history_id: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HistoryNodeRepr {
+    kind: String,
+    prev: Option<usize>,
+    syms: Vec<Symbol>,
+    values: Vec<u64>,
+    weight: Option<f64>,
+}
+
+impl Cfg {
+    /// Serializes the grammar, including its symbols and their names, rules and history,
+    /// into JSON.
+    pub fn to_json(&self) -> String {
+        let repr = CfgRepr {
+            num_syms: self.num_syms(),
+            names: (0..self.num_syms())
+                .map(|i| self.sym_source().name_of(i.into()).map(String::from))
+                .collect(),
+            rules: self
+                .rules()
+                .map(|rule| CfgRuleRepr {
+                    lhs: rule.lhs,
+                    rhs: rule.rhs.to_vec(),
+                    history_id: rule.history_id.get(),
+                })
+                .collect(),
+            history: self
+                .history_graph()
+                .iter()
+                .map(HistoryNodeRepr::from_node)
+                .collect(),
+        };
+        json::to_string(&repr)
+    }
+
+    /// Deserializes a grammar from JSON produced by `Cfg::to_json`.
+    pub fn from_json(input: &str) -> Result<Self> {
+        let repr: CfgRepr = json::from_str(input)?;
+        let mut cfg = Cfg::new();
+        cfg.sym_source_mut()
+            .generate()
+            .take(repr.num_syms)
+            .for_each(drop);
+        if repr.names.len() > repr.num_syms {
+            return Err(Error);
+        }
+        for (i, name) in repr.names.iter().enumerate() {
+            if let Some(name) = name {
+                cfg.sym_source_mut().set_name(i.into(), name);
+            }
+        }
+        // The first node is always the `NoOp` root that every history graph starts with.
+        for node in repr.history.iter().skip(1) {
+            let node = node.to_node()?;
+            if let HistoryNode::Linked { prev, ref node } = node {
+                if prev.get() >= cfg.history_graph().len() {
+                    return Err(Error);
+                }
+                if let LinkedHistoryNode::Merged { other } = *node {
+                    if other.get() >= cfg.history_graph().len() {
+                        return Err(Error);
+                    }
+                }
+            }
+            cfg.add_history_node(node);
+        }
+        for rule in &repr.rules {
+            let in_range = |sym: &Symbol| sym.usize() < repr.num_syms;
+            if !in_range(&rule.lhs) || !rule.rhs.iter().all(in_range) {
+                return Err(Error);
+            }
+            let history_id = match HistoryId::new(rule.history_id) {
+                Some(id) if id.get() < cfg.history_graph().len() => id,
+                _ => return Err(Error),
+            };
+            cfg.add_rule(RuleRef {
+                lhs: rule.lhs,
+                rhs: &rule.rhs[..],
+                history_id,
+            });
+        }
+        Ok(cfg)
+    }
+}
+
+impl HistoryNodeRepr {
+    fn new(kind: &str, prev: Option<HistoryId>) -> Self {
+        HistoryNodeRepr {
+            kind: kind.to_string(),
+            prev: prev.map(|id| id.get()),
+            syms: vec![],
+            values: vec![],
+            weight: None,
+        }
+    }
+
+    fn from_node(node: &HistoryNode) -> Self {
+        match *node {
+            HistoryNode::Linked { prev, ref node } => {
+                let prev = Some(prev);
+                match node {
+                    LinkedHistoryNode::Rhs { rhs } => HistoryNodeRepr {
+                        syms: rhs.clone(),
+                        ..Self::new("Rhs", prev)
+                    },
+                    &LinkedHistoryNode::Binarize { depth } => HistoryNodeRepr {
+                        values: vec![depth as u64],
+                        ..Self::new("Binarize", prev)
+                    },
+                    &LinkedHistoryNode::EliminateNulling { rhs0, rhs1, which } => {
+                        let which = match which {
+                            BinarizedRhsSubset::Left => 0,
+                            BinarizedRhsSubset::Right => 1,
+                            BinarizedRhsSubset::All => 2,
+                        };
+                        HistoryNodeRepr {
+                            syms: Some(rhs0).into_iter().chain(rhs1).collect(),
+                            values: vec![which],
+                            ..Self::new("EliminateNulling", prev)
+                        }
+                    }
+                    &LinkedHistoryNode::AssignPrecedence { looseness } => HistoryNodeRepr {
+                        values: vec![looseness as u64],
+                        ..Self::new("AssignPrecedence", prev)
+                    },
+                    &LinkedHistoryNode::RewriteSequence {
+                        top,
+                        rhs,
+                        sep,
+                        start,
+                        end,
+                    } => HistoryNodeRepr {
+                        syms: Some(rhs).into_iter().chain(sep).collect(),
+                        values: [top as u64, start as u64]
+                            .into_iter()
+                            .chain(end.map(|end| end as u64))
+                            .collect(),
+                        ..Self::new("RewriteSequence", prev)
+                    },
+                    &LinkedHistoryNode::Weight { weight } => HistoryNodeRepr {
+                        weight: Some(weight),
+                        ..Self::new("Weight", prev)
+                    },
+                    LinkedHistoryNode::Distances { events } => HistoryNodeRepr {
+                        values: events.iter().map(|&event| event as u64).collect(),
+                        ..Self::new("Distances", prev)
+                    },
+                    &LinkedHistoryNode::Label { id } => HistoryNodeRepr {
+                        values: vec![id as u64],
+                        ..Self::new("Label", prev)
+                    },
+                    &LinkedHistoryNode::Factor { pos, tail } => HistoryNodeRepr {
+                        values: vec![pos as u64, tail as u64],
+                        ..Self::new("Factor", prev)
+                    },
+                    &LinkedHistoryNode::FactorSubsequence { pos, len, inner } => HistoryNodeRepr {
+                        values: vec![pos as u64, len as u64, inner as u64],
+                        ..Self::new("FactorSubsequence", prev)
+                    },
+                    &LinkedHistoryNode::Merged { other } => HistoryNodeRepr {
+                        values: vec![other.get() as u64],
+                        ..Self::new("Merged", prev)
+                    },
+                }
+            }
+            HistoryNode::Root(root) => match root {
+                RootHistoryNode::NoOp => Self::new("NoOp", None),
+                RootHistoryNode::Rule { lhs } => HistoryNodeRepr {
+                    syms: vec![lhs],
+                    ..Self::new("Rule", None)
+                },
+                RootHistoryNode::Origin { origin } => HistoryNodeRepr {
+                    values: vec![origin as u64],
+                    ..Self::new("Origin", None)
+                },
+            },
+        }
+    }
+
+    fn to_node(&self) -> Result<HistoryNode> {
+        let sym = |i: usize| self.syms.get(i).cloned().ok_or(Error);
+        let value = |i: usize| {
+            self.values
+                .get(i)
+                .and_then(|&value| u32::try_from(value).ok())
+                .ok_or(Error)
+        };
+        let node = match &self.kind[..] {
+            "NoOp" => return Ok(RootHistoryNode::NoOp.into()),
+            "Rule" => return Ok(RootHistoryNode::Rule { lhs: sym(0)? }.into()),
+            "Origin" => {
+                let origin = *self.values.first().ok_or(Error)? as usize;
+                return Ok(RootHistoryNode::Origin { origin }.into());
+            }
+            "Rhs" => LinkedHistoryNode::Rhs {
+                rhs: self.syms.clone(),
+            },
+            "Binarize" => LinkedHistoryNode::Binarize { depth: value(0)? },
+            "EliminateNulling" => LinkedHistoryNode::EliminateNulling {
+                rhs0: sym(0)?,
+                rhs1: self.syms.get(1).cloned(),
+                which: match value(0)? {
+                    0 => BinarizedRhsSubset::Left,
+                    1 => BinarizedRhsSubset::Right,
+                    2 => BinarizedRhsSubset::All,
+                    _ => return Err(Error),
+                },
+            },
+            "AssignPrecedence" => LinkedHistoryNode::AssignPrecedence {
+                looseness: value(0)?,
+            },
+            "RewriteSequence" => LinkedHistoryNode::RewriteSequence {
+                top: value(0)? != 0,
+                rhs: sym(0)?,
+                sep: self.syms.get(1).cloned(),
+                // Older JSON has no bounds.
+                start: if self.values.len() > 1 { value(1)? } else { 0 },
+                end: if self.values.len() > 2 {
+                    Some(value(2)?)
+                } else {
+                    None
+                },
+            },
+            "Weight" => LinkedHistoryNode::Weight {
+                weight: self.weight.ok_or(Error)?,
+            },
+            "Distances" => LinkedHistoryNode::Distances {
+                events: (0..self.values.len())
+                    .map(value)
+                    .collect::<Result<Vec<_>>>()?,
+            },
+            "Label" => LinkedHistoryNode::Label { id: value(0)? },
+            "Factor" => LinkedHistoryNode::Factor {
+                pos: value(0)?,
+                tail: value(1)? != 0,
+            },
+            "FactorSubsequence" => LinkedHistoryNode::FactorSubsequence {
+                pos: value(0)?,
+                len: value(1)?,
+                inner: value(2)? != 0,
+            },
+            "Merged" => LinkedHistoryNode::Merged {
+                other: HistoryId::new(value(0)? as usize).ok_or(Error)?,
+            },
+            _ => return Err(Error),
+        };
+        let prev = self.prev.and_then(HistoryId::new).ok_or(Error)?;
+        Ok(HistoryNode::Linked { prev, node })
+    }
+}
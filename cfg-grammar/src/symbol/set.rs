@@ -5,9 +5,11 @@ use std::iter;
 use bit_vec;
 use bit_vec::BitVec;
 
+use crate::rhs_closure::RhsClosure;
 use crate::*;
 
 /// A set of symbols in the form of a bit vector.
+#[derive(Clone)]
 pub struct SymbolBitSet {
     bit_vec: BitVec,
 }
@@ -63,6 +65,31 @@ impl SymbolBitSet {
         set
     }
 
+    /// Constructs a set of nullable symbols, which derive the empty string.
+    pub fn nullable_set<G>(grammar: &G) -> Self
+    where
+        G: RuleContainer,
+    {
+        let mut set = SymbolBitSet::new(grammar, false);
+        RhsClosure::new(grammar).rhs_closure(&mut set.bit_vec);
+        set
+    }
+
+    /// Constructs a set of symbols that occur in any rule, either on the LHS or the RHS.
+    pub fn used<G>(grammar: &G) -> Self
+    where
+        G: RuleContainer,
+    {
+        let mut set = SymbolBitSet::new(grammar, false);
+        for rule in grammar.rules() {
+            set.set(rule.lhs, true);
+            for &sym in rule.rhs {
+                set.set(sym, true);
+            }
+        }
+        set
+    }
+
     /// Set the entry for a symbol.
     pub fn set(&mut self, sym: Symbol, value: bool) {
         self.bit_vec.set(sym.into(), value);
@@ -73,6 +100,19 @@ impl SymbolBitSet {
         self.bit_vec[sym.into()]
     }
 
+    /// Returns the number of symbols in this set.
+    pub fn count(&self) -> usize {
+        self.bit_vec
+            .blocks()
+            .map(|block| block.count_ones() as usize)
+            .sum()
+    }
+
+    /// Checks whether this set contains no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.bit_vec.none()
+    }
+
     /// Converts into a bit vector.
     pub fn into_bit_vec(self) -> BitVec {
         self.bit_vec
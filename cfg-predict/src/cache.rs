@@ -0,0 +1,87 @@
+//! Memoized prediction sets.
+
+use std::collections::BTreeSet;
+
+use cfg_grammar::{Cfg, RuleContainer};
+use cfg_symbol::Symbol;
+
+use super::{FirstSets, FollowSets, LastSets, PerSymbolSets, PredictTerminal};
+
+/// Memoizes FIRST, FOLLOW and LAST sets of a grammar. Sets are recomputed on demand
+/// once the grammar's revision changes.
+///
+/// A cache must be used with a single grammar.
+#[derive(Clone, Debug)]
+pub struct PredictCache {
+    start_sym: Symbol,
+    revision: Option<u64>,
+    first_sets: Option<PerSymbolSets>,
+    follow_sets: Option<PerSymbolSets>,
+    last_sets: Option<PerSymbolSets>,
+}
+
+impl PredictCache {
+    /// Creates an empty cache. The start symbol is used for FOLLOW sets.
+    pub fn new(start_sym: Symbol) -> Self {
+        PredictCache {
+            start_sym,
+            revision: None,
+            first_sets: None,
+            follow_sets: None,
+            last_sets: None,
+        }
+    }
+
+    /// Returns FIRST sets of the grammar.
+    pub fn first_sets(&mut self, grammar: &Cfg) -> &PerSymbolSets {
+        self.invalidate_if_changed(grammar);
+        self.first_sets
+            .get_or_insert_with(|| FirstSets::new(grammar).map)
+    }
+
+    /// Returns FOLLOW sets of the grammar.
+    pub fn follow_sets(&mut self, grammar: &Cfg) -> &PerSymbolSets {
+        if self.follow_sets.is_none() || self.revision != Some(grammar.revision()) {
+            let start_sym = self.start_sym;
+            let first_sets = self.first_sets(grammar);
+            let follow_sets = FollowSets::new(grammar, start_sym, first_sets).map;
+            self.follow_sets = Some(follow_sets);
+        }
+        self.follow_sets.as_ref().unwrap()
+    }
+
+    /// Returns the FOLLOW set of a single symbol, or an empty set for terminals. All FOLLOW
+    /// sets are computed and memoized on the first query.
+    pub fn follow_of(&mut self, grammar: &Cfg, sym: Symbol) -> BTreeSet<PredictTerminal> {
+        self.follow_sets(grammar)
+            .get(&sym)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns SLR reduce lookaheads, which pair every rule index with the FOLLOW set
+    /// of the rule's LHS.
+    pub fn slr_reductions(&mut self, grammar: &Cfg) -> Vec<(usize, BTreeSet<PredictTerminal>)> {
+        let follow_sets = self.follow_sets(grammar);
+        grammar
+            .indexed_rules()
+            .map(|(idx, rule)| (idx, follow_sets[&rule.lhs].clone()))
+            .collect()
+    }
+
+    /// Returns LAST sets of the grammar.
+    pub fn last_sets(&mut self, grammar: &Cfg) -> &PerSymbolSets {
+        self.invalidate_if_changed(grammar);
+        self.last_sets
+            .get_or_insert_with(|| LastSets::new(grammar).map)
+    }
+
+    fn invalidate_if_changed(&mut self, grammar: &Cfg) {
+        if self.revision != Some(grammar.revision()) {
+            self.revision = Some(grammar.revision());
+            self.first_sets = None;
+            self.follow_sets = None;
+            self.last_sets = None;
+        }
+    }
+}
@@ -35,6 +35,23 @@ where
         }
     }
 
+    /// Clears all computed distances without reallocating, so that `minimal_distances`
+    /// can be called again.
+    pub fn reset(&mut self) {
+        for (_, rule_distances) in &mut self.distances {
+            rule_distances
+                .iter_mut()
+                .for_each(|distance| *distance = None);
+        }
+        for distances in [
+            &mut self.prediction_distances,
+            &mut self.completion_distances,
+            &mut self.min_of,
+        ] {
+            distances.iter_mut().for_each(|distance| *distance = None);
+        }
+    }
+
     /// Returns distances in order respective to the order of rule iteration.
     pub fn distances(&self) -> &[(HistoryId, Vec<Option<u32>>)] {
         &self.distances[..]
@@ -55,19 +72,13 @@ where
         for terminal in terminal_set.iter() {
             self.min_of[terminal.usize()] = Some(1);
         }
-        // The distance for nullable symbols is 0.
-        for rule in self.grammar.rules() {
-            if rule.rhs.is_empty() {
-                self.min_of[rule.lhs.usize()] = Some(0);
-            }
-        }
-        // Calculate minimal lengths for nonterminals.
+        // Calculate minimal lengths for nonterminals. The distance for nullable symbols is 0.
         RhsClosure::new(self.grammar).rhs_closure_with_values(&mut self.min_of);
     }
 
     fn immediate_minimal_distances(&mut self) {
         // Calculates distances within rules.
-        for (idx, rule) in self.grammar.rules().enumerate() {
+        for (idx, rule) in self.grammar.indexed_rules() {
             let mut history = &self.grammar.history_graph()[rule.history_id.get()];
             let mut positions = &[][..];
             while let &HistoryNode::Linked { prev, ref node } = history {
@@ -89,7 +100,7 @@ where
         while changed {
             // Keep going for as long as any completion distances were lowered in the last pass.
             changed = false;
-            for (idx, rule) in self.grammar.rules().enumerate() {
+            for (idx, rule) in self.grammar.indexed_rules() {
                 if let Some(distance) = self.completion_distances[rule.lhs.usize()] {
                     let (_, changed_now) = self.update_rule_distances(distance, rule.rhs, idx);
                     changed |= changed_now;
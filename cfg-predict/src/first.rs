@@ -6,12 +6,12 @@ use cfg_grammar::symbol::set::SymbolBitSet;
 use cfg_grammar::RuleContainer;
 use cfg_symbol::Symbol;
 
-use super::{PerSymbolSets, PredictSets};
+use super::{PerSymbolSets, PredictSets, PredictTerminal};
 
 /// Collector of FIRST sets.
 pub struct FirstSets<'a, G> {
     pub(super) map: PerSymbolSets,
-    lookahead: Vec<Option<Symbol>>,
+    lookahead: Vec<PredictTerminal>,
     changed: bool,
     terminal_set: SymbolBitSet,
     grammar: &'a G,
@@ -42,17 +42,17 @@ where
     }
 
     /// Calculates a FIRST set for a string of symbols.
-    pub fn first_set_for_string(&self, string: &[Symbol]) -> BTreeSet<Option<Symbol>> {
+    pub fn first_set_for_string(&self, string: &[Symbol]) -> BTreeSet<PredictTerminal> {
         let mut result = BTreeSet::new();
         for &sym in string {
             let result_cardinality = result.len();
             if self.terminal_set.has_sym(sym) {
-                result.insert(Some(sym));
+                result.insert(PredictTerminal::Terminal(sym));
             } else {
                 let first_set = self.map.get(&sym).unwrap();
-                for &maybe_terminal in first_set {
-                    if maybe_terminal.is_some() {
-                        result.insert(maybe_terminal);
+                for &terminal in first_set {
+                    if terminal != PredictTerminal::Epsilon {
+                        result.insert(terminal);
                     }
                 }
             }
@@ -61,7 +61,7 @@ where
             }
         }
         if result.is_empty() {
-            result.insert(None);
+            result.insert(PredictTerminal::Epsilon);
         }
         result
     }
@@ -90,7 +90,7 @@ where
         for &sym in rhs {
             let mut nullable = false;
             if self.terminal_set.has_sym(sym) {
-                self.lookahead.push(Some(sym));
+                self.lookahead.push(PredictTerminal::Terminal(sym));
             } else {
                 match self.map.get(&sym) {
                     None => {
@@ -101,11 +101,11 @@ where
                         // should be empty.
                     }
                     Some(set) => {
-                        for &maybe_terminal in set {
-                            if maybe_terminal.is_some() {
-                                self.lookahead.push(maybe_terminal);
-                            } else {
+                        for &terminal in set {
+                            if terminal == PredictTerminal::Epsilon {
                                 nullable = true;
+                            } else {
+                                self.lookahead.push(terminal);
                             }
                         }
                     }
@@ -116,7 +116,7 @@ where
                 return;
             }
         }
-        self.lookahead.push(None);
+        self.lookahead.push(PredictTerminal::Epsilon);
     }
 }
 
@@ -7,12 +7,12 @@ use cfg_symbol::Symbol;
 use cfg_grammar::symbol::set::SymbolBitSet;
 use cfg_grammar::RuleContainer;
 
-use super::{PerSymbolSets, PredictSets};
+use super::{PerSymbolSets, PredictSets, PredictTerminal};
 
 /// FOLLOW sets.
 pub struct FollowSets {
     /// Mapping from nonterminals to FOLLOW sets.
-    map: PerSymbolSets,
+    pub(super) map: PerSymbolSets,
 }
 
 impl FollowSets {
@@ -29,7 +29,7 @@ impl FollowSets {
         for rule in grammar.rules() {
             let follow_set = this.map.entry(rule.lhs).or_insert_with(BTreeSet::new);
             if rule.lhs == start_sym {
-                follow_set.insert(None);
+                follow_set.insert(PredictTerminal::EndOfInput);
             }
         }
 
@@ -43,7 +43,7 @@ impl FollowSets {
                 for &sym in rule.rhs.iter().rev() {
                     if terminal_set.has_sym(sym) {
                         follow_set.clear();
-                        follow_set.insert(Some(sym));
+                        follow_set.insert(PredictTerminal::Terminal(sym));
                     } else {
                         let followed = this.map.get_mut(&sym).unwrap();
                         let prev_cardinality = followed.len();
@@ -51,10 +51,15 @@ impl FollowSets {
                         changed |= prev_cardinality != followed.len();
 
                         let first_set = first_sets.get(&sym).unwrap();
-                        if !first_set.contains(&None) {
+                        if !first_set.contains(&PredictTerminal::Epsilon) {
                             follow_set.clear();
                         }
-                        follow_set.extend(first_set.iter().cloned());
+                        follow_set.extend(
+                            first_set
+                                .iter()
+                                .cloned()
+                                .filter(|&terminal| terminal != PredictTerminal::Epsilon),
+                        );
                     }
                 }
             }
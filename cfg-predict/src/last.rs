@@ -8,7 +8,7 @@ use super::PredictSets;
 
 /// FIRST sets.
 pub struct LastSets {
-    map: PerSymbolSets,
+    pub(super) map: PerSymbolSets,
 }
 
 impl LastSets {
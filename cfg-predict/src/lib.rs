@@ -2,14 +2,18 @@
 //!
 //! FIRST and FOLLOW sets impls are based on code by Niko Matsakis.
 
+mod cache;
 mod distance;
 mod first;
 mod follow;
 mod last;
+mod report;
 mod sets;
 
+pub use self::cache::PredictCache;
 pub use self::distance::MinimalDistance;
 pub use self::first::FirstSets;
 pub use self::follow::FollowSets;
 pub use self::last::LastSets;
-pub use self::sets::{PerSymbolSets, PredictSets};
+pub use self::report::{PredictReport, PredictReportEntry};
+pub use self::sets::{PerSymbolSets, PredictSets, PredictTerminal};
@@ -0,0 +1,51 @@
+//! Combined FIRST and FOLLOW sets.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use cfg_grammar::RuleContainer;
+use cfg_symbol::Symbol;
+
+use super::{FirstSets, FollowSets, PredictSets, PredictTerminal};
+
+/// FIRST and FOLLOW sets of every nonterminal, together with nullability.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PredictReport {
+    /// Mapping from nonterminals to their entries.
+    pub entries: BTreeMap<Symbol, PredictReportEntry>,
+}
+
+/// Prediction sets of a single nonterminal.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PredictReportEntry {
+    /// The FIRST set. Contains `Epsilon` if the nonterminal is nullable.
+    pub first: BTreeSet<PredictTerminal>,
+    /// The FOLLOW set. Contains `EndOfInput` if the nonterminal may occur at the end of input.
+    pub follow: BTreeSet<PredictTerminal>,
+    /// Indicates whether the nonterminal derives the empty string.
+    pub nullable: bool,
+}
+
+impl PredictReport {
+    /// Computes FIRST and FOLLOW sets of the grammar. FIRST sets are computed once and shared
+    /// with the computation of FOLLOW sets.
+    pub fn new<G>(grammar: &G, start_sym: Symbol) -> Self
+    where
+        G: RuleContainer,
+    {
+        let first_sets = FirstSets::new(grammar);
+        let follow_sets = FollowSets::new(grammar, start_sym, first_sets.predict_sets());
+        let entries = first_sets
+            .predict_sets()
+            .iter()
+            .map(|(&nonterminal, first)| {
+                let entry = PredictReportEntry {
+                    first: first.clone(),
+                    follow: follow_sets.predict_sets()[&nonterminal].clone(),
+                    nullable: first.contains(&PredictTerminal::Epsilon),
+                };
+                (nonterminal, entry)
+            })
+            .collect();
+        PredictReport { entries }
+    }
+}
@@ -3,7 +3,28 @@ use std::collections::{BTreeMap, BTreeSet};
 use cfg_symbol::Symbol;
 
 /// The representation of FIRST and FOLLOW sets.
-pub type PerSymbolSets = BTreeMap<Symbol, BTreeSet<Option<Symbol>>>;
+pub type PerSymbolSets = BTreeMap<Symbol, BTreeSet<PredictTerminal>>;
+
+/// An element of a FIRST, FOLLOW or LAST set.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PredictTerminal {
+    /// A terminal symbol.
+    Terminal(Symbol),
+    /// The empty string. Occurs in FIRST and LAST sets of nullable symbols.
+    Epsilon,
+    /// The end of input. Occurs in FOLLOW sets of symbols that may end the input.
+    EndOfInput,
+}
+
+impl PredictTerminal {
+    /// Returns the terminal symbol, if any.
+    pub fn terminal(self) -> Option<Symbol> {
+        match self {
+            PredictTerminal::Terminal(sym) => Some(sym),
+            _ => None,
+        }
+    }
+}
 
 pub trait PredictSets {
     fn predict_sets(&self) -> &PerSymbolSets;
@@ -3,7 +3,7 @@
 use std::ops::{Bound, RangeBounds};
 
 use crate::destination::SequenceDestination;
-use crate::{Separator, Sequence};
+use crate::{Separator, SeparatorMode, Sequence};
 use cfg_grammar::history::HistoryId;
 use cfg_symbol::Symbol;
 
@@ -60,7 +60,7 @@ where
 
     /// Sets proper separation with the given separator symbol.
     pub fn intersperse(self, sym: Symbol) -> Self {
-        self.separator(Separator::Proper(sym))
+        self.separator(Separator::from_mode(sym, SeparatorMode::Proper))
     }
 
     /// Assigns the rule history, which is used on the next call to `rhs`, or overwritten by a call
@@ -101,8 +101,20 @@ where
     }
 
     /// Adds a sequence rule to the grammar.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is missing or the minimum number of repetitions exceeds the maximum.
     pub fn rhs_with_history(mut self, rhs: Symbol, history_id: Option<HistoryId>) -> Self {
         let (start, end) = self.range.take().expect("expected inclusive(n, m)");
+        if let Some(end) = end {
+            assert!(
+                start <= end,
+                "invalid sequence range: start {} is greater than end {}",
+                start,
+                end
+            );
+        }
         self.destination.add_sequence(Sequence {
             lhs: self.lhs.unwrap(),
             rhs,
@@ -44,6 +44,61 @@ pub enum Separator {
     Null,
 }
 
+/// The mode of separation in a sequence, without the separator symbol.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SeparatorMode {
+    /// See `Separator::Proper`.
+    Proper,
+    /// See `Separator::Trailing`.
+    Trailing,
+    /// See `Separator::Liberal`.
+    Liberal,
+}
+
+/// The way bounded repetitions are split into smaller ones during rewriting.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum SequenceDecomposition {
+    /// Splits off the largest power of two that is smaller than the number of repetitions.
+    #[default]
+    PowerOfTwo,
+    /// Splits off one repetition at a time, as in `seq_n ::= item seq_{n-1}`.
+    Linear,
+    /// Splits repetitions into two halves.
+    Balanced,
+}
+
+impl SequenceDecomposition {
+    /// Returns the number of repetitions to split off from `n` repetitions, where `n >= 2`.
+    fn split(self, n: u32) -> u32 {
+        match self {
+            SequenceDecomposition::PowerOfTwo => n.next_power_of_two() / 2,
+            SequenceDecomposition::Linear => 1,
+            SequenceDecomposition::Balanced => n / 2,
+        }
+    }
+}
+
+/// Replaces every sequence with `Liberal` separation by two sequences with the same LHS, one
+/// with `Proper` and one with `Trailing` separation. The language stays the same.
+///
+/// Only the `Proper` half may be empty, so that the LHS does not get two nulling rules.
+pub fn normalize_liberal_separators(sequences: &mut Vec<Sequence>) {
+    let mut normalized = Vec::with_capacity(sequences.len());
+    for &seq in sequences.iter() {
+        match seq.separator {
+            Liberal(sep) => {
+                normalized.push(seq.separator(Proper(sep)));
+                if seq.end != Some(0) {
+                    let trailing = seq.inclusive(seq.start.max(1), seq.end);
+                    normalized.push(trailing.separator(Trailing(sep)));
+                }
+            }
+            _ => normalized.push(seq),
+        }
+    }
+    *sequences = normalized;
+}
+
 impl Sequence {
     /// Assigns the inclusive range of the number of repetitions.
     pub fn inclusive(mut self, start: u32, end: Option<u32>) -> Self {
@@ -73,6 +128,15 @@ impl Sequence {
 }
 
 impl Separator {
+    /// Creates a separator with the given symbol and mode of separation.
+    pub fn from_mode(sym: Symbol, mode: SeparatorMode) -> Self {
+        match mode {
+            SeparatorMode::Proper => Proper(sym),
+            SeparatorMode::Trailing => Trailing(sym),
+            SeparatorMode::Liberal => Liberal(sym),
+        }
+    }
+
     /// Returns the kind of separation for a prefix sequence.
     pub fn prefix_separator(self) -> Self {
         match self {
@@ -8,8 +8,8 @@ use std::collections::HashMap;
 use crate::builder::SequenceRuleBuilder;
 use crate::destination::SequenceDestination;
 use crate::Separator::{self, Liberal, Proper, Trailing};
-use crate::Sequence;
 use crate::Symbol;
+use crate::{Sequence, SequenceDecomposition};
 use cfg_grammar::history::node::{HistoryId, HistoryNodeRewriteSequence, RootHistoryNode};
 use cfg_grammar::rule::builder::RuleBuilder;
 use cfg_grammar::rule_container::RuleContainer;
@@ -24,6 +24,7 @@ where
     map: HashMap<PartialSequence, Symbol>,
     top: Option<HistoryId>,
     lhs: Option<Symbol>,
+    decomposition: SequenceDecomposition,
 }
 
 // A key into a private map.
@@ -67,9 +68,16 @@ where
             map: HashMap::new(),
             top: None,
             lhs: None,
+            decomposition: SequenceDecomposition::default(),
         }
     }
 
+    /// Sets the way bounded repetitions are split into smaller ones.
+    pub fn decomposition(mut self, decomposition: SequenceDecomposition) -> Self {
+        self.decomposition = decomposition;
+        self
+    }
+
     /// Rewrites sequence rules.
     pub fn rewrite_sequences(sequence_rules: &[Sequence], rule_container: &'a mut D) {
         let sequences = SequencesToProductions::new(rule_container);
@@ -96,6 +104,8 @@ where
                 top: true,
                 rhs: top.rhs,
                 sep: top.separator.into(),
+                start: top.start,
+                end: top.end,
                 prev,
             }
             .into(),
@@ -111,6 +121,8 @@ where
                 top: false,
                 rhs: top.rhs,
                 sep: top.separator.into(),
+                start: top.start,
+                end: top.end,
                 prev,
             }
             .into(),
@@ -203,11 +215,11 @@ where
             }
             (_, 1, Some(end)) => {
                 // end >= 3
-                let pow2 = end.next_power_of_two() / 2;
+                let split = self.decomposition.split(end);
                 let (seq1, block, seq2) = (
-                    sequence.clone().range(1..=pow2),
-                    sequence.clone().range(pow2..=pow2),
-                    sequence.clone().range(1..=end - pow2),
+                    sequence.clone().range(1..=split),
+                    sequence.clone().range(split..=split),
+                    sequence.clone().range(1..=end - split),
                 );
                 let rhs1 = self.recurse(&seq1);
                 let block = self.recurse(&block.separator(separator.prefix_separator()));
@@ -226,10 +238,10 @@ where
                 // to do infinity
                 let (seq1, seq2) = if Some(start) == end {
                     // A "block"
-                    let pow2 = start.next_power_of_two() / 2;
+                    let split = self.decomposition.split(start);
                     (
-                        sequence.clone().range(pow2..=pow2),
-                        sequence.clone().range(start - pow2..=start - pow2),
+                        sequence.clone().range(split..=split),
+                        sequence.clone().range(start - split..=start - split),
                     )
                 } else {
                     // A "span"
@@ -6,4 +6,4 @@ mod repr;
 pub mod source;
 
 pub use self::repr::Symbol;
-pub use self::source::SymbolSource;
+pub use self::source::{SymbolCheckpoint, SymbolSource};
@@ -1,5 +1,9 @@
 //! Source
 
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
 use super::repr::{SymbolRepr, FIRST_ID, NULL_ID};
 
 use crate::*;
@@ -9,12 +13,24 @@ use crate::*;
 #[derive(Clone, Debug, Default)]
 pub struct SymbolSource {
     next_id: SymbolRepr,
+    /// Names of symbols, indexed by symbol ID. May be shorter than the number of symbols.
+    names: Vec<Option<Rc<str>>>,
+}
+
+/// A saved state of a `SymbolSource`, which it can be rolled back to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SymbolCheckpoint {
+    next_id: SymbolRepr,
+    names_len: usize,
 }
 
 impl SymbolSource {
     /// Creates a source of numeric symbols with an empty symbol space.
     pub fn new() -> Self {
-        Self { next_id: FIRST_ID }
+        Self {
+            next_id: FIRST_ID,
+            names: vec![],
+        }
     }
     /// Returns generated symbols.
     pub fn sym<const N: usize>(&mut self) -> [Symbol; N] {
@@ -24,6 +40,54 @@ impl SymbolSource {
         }
         result
     }
+    /// Returns generated symbols with the given names.
+    pub fn with_names<const N: usize>(&mut self, names: [&str; N]) -> [Symbol; N] {
+        let result = self.sym();
+        for (&sym, name) in result.iter().zip(names) {
+            self.set_name(sym, name);
+        }
+        result
+    }
+    /// Assigns a name to a symbol.
+    pub fn set_name(&mut self, sym: Symbol, name: &str) {
+        if self.names.len() <= sym.usize() {
+            self.names.resize(sym.usize() + 1, None);
+        }
+        self.names[sym.usize()] = Some(name.into());
+    }
+    /// Returns the name of a symbol, if it has one.
+    pub fn name_of(&self, sym: Symbol) -> Option<&str> {
+        self.names.get(sym.usize()).and_then(|name| name.as_deref())
+    }
+    /// Returns the first symbol with the given name, if any.
+    pub fn find_by_name(&self, name: &str) -> Option<Symbol> {
+        self.names
+            .iter()
+            .position(|sym_name| sym_name.as_deref() == Some(name))
+            .map(Symbol::from)
+    }
+    /// Returns a map from names to named symbols. If several symbols share a name,
+    /// the first one is kept, as in `find_by_name`.
+    pub fn name_to_symbol(&self) -> HashMap<String, Symbol> {
+        let mut map = HashMap::new();
+        for (id, name) in self.names.iter().enumerate() {
+            if let Some(name) = name {
+                map.entry(name.to_string())
+                    .or_insert_with(|| Symbol::from(id));
+            }
+        }
+        map
+    }
+    /// Returns the symbol with the given name. Generates a new named symbol if there is none.
+    pub fn intern_name(&mut self, name: &str) -> Symbol {
+        match self.find_by_name(name) {
+            Some(sym) => sym,
+            None => {
+                let [sym] = self.with_names([name]);
+                sym
+            }
+        }
+    }
     /// Generates a new unique symbol.
     pub fn next_sym(&mut self) -> Symbol {
         let ret = self.next_id.into();
@@ -31,10 +95,43 @@ impl SymbolSource {
         debug_assert_ne!(self.next_id, NULL_ID, "ran out of Symbol space?");
         ret
     }
+    /// Generates `n` new symbols and returns the contiguous range of their IDs. The IDs can
+    /// be turned into symbols with `Symbol::from`.
+    pub fn reserve(&mut self, n: usize) -> Range<usize> {
+        let start = self.num_syms();
+        for _ in 0..n {
+            self.next_sym();
+        }
+        start..self.num_syms()
+    }
     /// Returns the number of symbols in use.
     pub fn num_syms(&self) -> usize {
         self.next_id as usize
     }
+    /// Saves the number of symbols in use, along with their names.
+    pub fn checkpoint(&self) -> SymbolCheckpoint {
+        SymbolCheckpoint {
+            next_id: self.next_id,
+            names_len: self.names.len(),
+        }
+    }
+    /// Forgets all symbols generated after the checkpoint was taken.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the checkpoint is ahead of this source, which happens when it was taken
+    /// before an earlier rollback or from another source.
+    pub fn rollback(&mut self, checkpoint: SymbolCheckpoint) {
+        assert!(
+            checkpoint.next_id <= self.next_id,
+            "cannot roll forward to a checkpoint with {} symbols from {} symbols",
+            checkpoint.next_id,
+            self.next_id
+        );
+        self.next_id = checkpoint.next_id;
+        self.names
+            .truncate(checkpoint.names_len.min(self.next_id as usize));
+    }
     /// Returns an iterator that generates symbols.
     pub fn generate(&mut self) -> Generate {
         Generate { source: self }
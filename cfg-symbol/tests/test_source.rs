@@ -0,0 +1,45 @@
+use cfg_symbol::{Symbol, SymbolSource};
+
+#[test]
+fn test_checkpoint_rollback() {
+    let mut source = SymbolSource::new();
+    let [a] = source.with_names(["a"]);
+    let checkpoint = source.checkpoint();
+    let [b, _c] = source.with_names(["b", "c"]);
+    assert_eq!(source.num_syms(), 3);
+
+    source.rollback(checkpoint);
+    assert_eq!(source.num_syms(), 1);
+    assert_eq!(source.name_of(a), Some("a"));
+    assert_eq!(source.name_of(b), None);
+    assert_eq!(source.find_by_name("b"), None);
+    let [d] = source.sym();
+    assert_eq!(d, b);
+}
+
+#[test]
+#[should_panic]
+fn test_rollback_forward() {
+    let mut source = SymbolSource::new();
+    let checkpoint = source.checkpoint();
+    let [_a, _b] = source.sym();
+    let later = source.checkpoint();
+    source.rollback(checkpoint);
+    source.rollback(later);
+}
+
+#[test]
+fn test_reserve() {
+    let mut source = SymbolSource::new();
+    let [a] = source.sym();
+    let range = source.reserve(10);
+    assert_eq!(range.len(), 10);
+    assert_eq!(source.num_syms(), 11);
+    assert_eq!(range.start, a.usize() + 1);
+
+    let syms: Vec<Symbol> = range.map(Symbol::from).collect();
+    source.set_name(syms[9], "last");
+    assert_eq!(source.find_by_name("last"), Some(syms[9]));
+    let [b] = source.sym();
+    assert_eq!(b.usize(), 11);
+}
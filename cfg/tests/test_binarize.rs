@@ -3,6 +3,8 @@
 mod support;
 
 use cfg::classify::useful::Usefulness;
+use cfg::rhs_closure::RhsClosure;
+use cfg::symbol::set::SymbolBitSet;
 use cfg::{BinarizedCfg, Cfg, RuleContainer};
 
 #[test]
@@ -108,3 +110,145 @@ fn test_binarize_very_long_rule() {
     equivalent.rule(start).rhs(long_rhs);
     support::assert_eq_rules(equivalent.rules(), cfg.rules());
 }
+
+#[test]
+fn test_apply_nulling() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x, b])
+        .rule(a)
+        .rhs([])
+        .rule(b)
+        .rhs([]);
+
+    let mut cfg = cfg.binarize();
+    let nulling = cfg.eliminate_nulling_rules();
+    let nulled = nulling.apply_nulling(&cfg);
+
+    let main_rules: Vec<_> = cfg.rules().collect();
+    let nulling_rules: Vec<_> = nulling.rules().collect();
+    let mut described: Vec<_> = nulled
+        .iter()
+        .map(|(&(rule_idx, position), nulling_indices)| {
+            let rule = main_rules[rule_idx];
+            let lhs_of_nulled: Vec<_> = nulling_indices
+                .iter()
+                .map(|&idx| nulling_rules[idx].lhs)
+                .collect();
+            (rule.lhs, rule.rhs.to_vec(), position, lhs_of_nulled)
+        })
+        .collect();
+    described.sort();
+
+    let g0 = main_rules
+        .iter()
+        .find(|rule| rule.lhs == start)
+        .unwrap()
+        .rhs[0];
+    let mut expected = vec![(start, vec![g0], 1, vec![b]), (g0, vec![x], 0, vec![a])];
+    expected.sort();
+    assert_eq!(described, expected);
+}
+
+#[test]
+fn test_binarize_merge_histories_on_dedup() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, c, d, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, b, c, x]).rhs([a, b, d, y]);
+    cfg.set_merge_histories_on_dedup(true);
+
+    let binarized = cfg.binarize();
+    let shared: Vec<_> = binarized
+        .rules()
+        .filter(|rule| rule.rhs == [a, b])
+        .collect();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(binarized.rules().count(), 5);
+
+    cfg.set_merge_histories_on_dedup(false);
+    let binarized = cfg.binarize();
+    assert_eq!(
+        binarized.rules().filter(|rule| rule.rhs == [a, b]).count(),
+        2
+    );
+}
+
+#[test]
+fn test_eliminate_nulling_rules_reporting() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rhs([x])
+        .rule(a)
+        .rhs([b, b])
+        .rule(b)
+        .rhs([]);
+
+    let mut binarized = cfg.binarize();
+    let (nulling, lost) = binarized.eliminate_nulling_rules_reporting();
+
+    assert_eq!(lost, vec![a]);
+    assert!(binarized.rules().all(|rule| rule.lhs == start));
+    assert!(nulling.rules().any(|rule| rule.lhs == a));
+}
+
+#[test]
+fn test_auto_label() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, c, d] = cfg.named_syms(["start", "a", "b", "c", "d"]);
+
+    cfg.rule(start).rhs([a, b, c, d]);
+
+    let mut binarized = cfg.binarize();
+    binarized.auto_label();
+
+    let names: Vec<_> = binarized
+        .rules()
+        .map(|rule| {
+            binarized
+                .sym_source()
+                .name_of(rule.lhs)
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(names, vec!["start", "start.1", "start.2"]);
+}
+
+#[test]
+fn test_reinsert_nulling() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x] = cfg.sym();
+
+    cfg.rule(start).rhs([a, a]).rule(a).rhs([]).rhs([x]);
+
+    let nullable = |grammar: &BinarizedCfg| {
+        let mut nullable = SymbolBitSet::new(grammar, false);
+        for rule in grammar.rules() {
+            if rule.rhs.is_empty() {
+                nullable.set(rule.lhs, true);
+            }
+        }
+        let mut nullable = nullable.into_bit_vec();
+        RhsClosure::new(grammar).rhs_closure(&mut nullable);
+        nullable[start.usize()]
+    };
+
+    let mut binarized = cfg.binarize();
+    assert!(nullable(&binarized));
+    let nulling = binarized.eliminate_nulling_rules();
+    assert!(!nullable(&binarized));
+    let num_rules = binarized.rules().count();
+
+    binarized.reinsert_nulling(&nulling);
+    assert!(nullable(&binarized));
+    assert_eq!(binarized.rules().count(), num_rules + 1);
+    assert!(binarized
+        .rules()
+        .any(|rule| rule.lhs == a && rule.rhs.is_empty()));
+}
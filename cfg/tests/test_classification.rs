@@ -4,7 +4,11 @@ use std::rc::Rc;
 
 #[cfg(feature = "ll")]
 use cfg::classify::ll::{LlNonterminalClass, LlParseTable};
-use cfg::classify::lr::{Lr0FsmBuilder, Lr0Item, Lr0Items, Lr0Node};
+use cfg::classify::lr::{
+    fsm_stats, reduce_items, FsmStats, Lr0FsmBuilder, Lr0Item, Lr0Items, Lr0Node,
+};
+use cfg::classify::recursive::RecursionSummary;
+use cfg::classify::stats::GrammarStats;
 use cfg::{Cfg, RuleContainer};
 
 use std::collections::BTreeMap;
@@ -178,3 +182,101 @@ fn test_lr0() {
 
     assert_eq!(nodes, lr0_fsm);
 }
+
+#[test]
+fn test_lr0_fsm_stats() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([x, start]).rhs([y]);
+
+    let lr0_fsm = Lr0FsmBuilder::new(&mut cfg).make_lr0_fsm(start);
+    assert_eq!(
+        fsm_stats(&lr0_fsm),
+        FsmStats {
+            states: 3,
+            shift_edges: 4,
+            reduce_items: 1,
+            conflicts: 0,
+        }
+    );
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([x]).rhs([x, y]);
+
+    let lr0_fsm = Lr0FsmBuilder::new(&mut cfg).make_lr0_fsm(start);
+    assert_eq!(
+        fsm_stats(&lr0_fsm),
+        FsmStats {
+            states: 3,
+            shift_edges: 2,
+            reduce_items: 2,
+            conflicts: 1,
+        }
+    );
+}
+
+#[test]
+fn test_lr0_reduce_items() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([x, start]).rhs([y]);
+
+    let lr0_fsm = Lr0FsmBuilder::new(&mut cfg).make_lr0_fsm(start);
+    let items: Vec<_> = reduce_items(&lr0_fsm).collect();
+    // The rule `start ::= y` is reduced in the final state reached after shifting `y`.
+    assert_eq!(items, vec![(2, 1)]);
+    assert_eq!(lr0_fsm[0].link[&y], 2);
+    assert!(lr0_fsm[2].link.is_empty());
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([x]).rhs([x, y]);
+
+    let lr0_fsm = Lr0FsmBuilder::new(&mut cfg).make_lr0_fsm(start);
+    let items: Vec<_> = reduce_items(&lr0_fsm).collect();
+    assert_eq!(items, vec![(1, 0), (2, 1)]);
+    assert_eq!(items.len(), fsm_stats(&lr0_fsm).reduce_items);
+}
+
+#[test]
+fn test_grammar_stats() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, list, opt, x, y, _unused] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([list, opt])
+        .rule(list)
+        .rhs([list, x])
+        .rhs([x])
+        .rule(opt)
+        .rhs([y, y, y])
+        .rhs([]);
+
+    assert_eq!(
+        GrammarStats::new(&cfg),
+        GrammarStats {
+            symbols: 6,
+            rules: 5,
+            terminals: 2,
+            nonterminals: 3,
+            nullable: 1,
+            max_rhs_len: 3,
+            avg_rhs_len: 1.6,
+            cycle_free: true,
+            recursion: RecursionSummary {
+                left: 1,
+                right: 0,
+                middle: 0,
+                all: 0,
+            },
+        }
+    );
+
+    cfg.rule(x).rhs([list]);
+    assert!(!GrammarStats::new(&cfg).cycle_free);
+}
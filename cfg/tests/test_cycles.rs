@@ -89,3 +89,35 @@ fn test_cycle_branch() {
     support::assert_eq_rules(equivalent.rules(), cfg.rules());
     assert!(Cycles::new(&mut cfg).cycle_free());
 }
+
+#[test]
+fn test_nullable_cycles() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rule(a)
+        .rhs([a, b])
+        .rhs([x])
+        .rule(b)
+        .rhs([]);
+
+    let cycles = Cycles::new(&mut cfg);
+    assert!(cycles.cycle_free());
+    assert_eq!(cycles.cycle_participants().count(), 0);
+    assert!(cycles.nullable_cycles());
+    let participants: Vec<_> = cycles
+        .nullable_cycle_participants()
+        .iter()
+        .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+        .collect();
+    assert_eq!(participants, vec![(a, vec![a, b])]);
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([a, x]).rhs([x]);
+
+    assert!(!Cycles::new(&mut cfg).nullable_cycles());
+}
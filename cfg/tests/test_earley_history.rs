@@ -0,0 +1,381 @@
+#![cfg(feature = "cfg-earley")]
+
+use cfg::earley::history::RuleDot;
+use cfg::earley::Grammar;
+use cfg::history::graph::HistoryError;
+use cfg::history::node::{LinkedHistoryNode, RootHistoryNode};
+use cfg::rule::RuleRef;
+use cfg::{HistoryId, HistoryNode, RuleContainer};
+
+#[test]
+fn test_final_history_deep_chain() {
+    let mut grammar = Grammar::new();
+    let [start, x] = grammar.sym();
+    grammar.rule(start).rhs([x]);
+    grammar.set_start(start);
+
+    let mut history_id = grammar.rules().next().unwrap().history_id;
+    for i in 0..100_000 {
+        history_id = grammar.add_history_node(HistoryNode::Linked {
+            prev: history_id,
+            node: LinkedHistoryNode::Weight { weight: i as f64 },
+        });
+    }
+    let [lhs] = grammar.sym();
+    grammar.rule(lhs).history(history_id).rhs([x]);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    assert_eq!(histories.len(), binarized.history_graph().len());
+    let last_rule = binarized.rules().last().unwrap();
+    assert_eq!(histories[last_rule.history_id.get()].weight, Some(99_999.0));
+}
+
+#[test]
+fn test_rule_dot_merge() {
+    let mut grammar = Grammar::new();
+    let [a, b, ev] = grammar.sym();
+    let near = RuleDot {
+        event: None,
+        distance: Some(a),
+    };
+    let far = RuleDot {
+        event: Some((Some(ev), (1, 2))),
+        distance: Some(b),
+    };
+
+    let merged = far.merge(near);
+    assert_eq!(merged.distance, Some(a));
+    assert_eq!(merged.event, Some((Some(ev), (1, 2))));
+    let merged = near.merge(far);
+    assert_eq!(merged.distance, Some(a));
+    assert_eq!(merged.event, Some((Some(ev), (1, 2))));
+
+    let merged = RuleDot::none().merge(RuleDot::none());
+    assert_eq!(merged.distance, None);
+    assert_eq!(merged.event, None);
+    let merged = RuleDot::none().merge(far);
+    assert_eq!(merged.distance, Some(b));
+    assert_eq!(merged.trace(), Some((1, 2)));
+}
+
+#[test]
+fn test_validate_history() {
+    let mut grammar = Grammar::new();
+    let [start, x] = grammar.sym();
+    grammar.rule(start).rhs([x]);
+    grammar.set_start(start);
+    assert_eq!(grammar.validate_history(), Ok(()));
+
+    let history_id = grammar.rules().next().unwrap().history_id;
+    let forward = HistoryId::new(grammar.history_graph().len() + 1).unwrap();
+    let node = grammar.add_history_node(HistoryNode::Linked {
+        prev: forward,
+        node: LinkedHistoryNode::Weight { weight: 1.0 },
+    });
+    grammar.add_history_node(HistoryNode::Linked {
+        prev: history_id,
+        node: LinkedHistoryNode::Weight { weight: 2.0 },
+    });
+    assert_eq!(
+        grammar.validate_history(),
+        Err(HistoryError::ForwardReference {
+            node: node.get(),
+            prev: forward,
+        })
+    );
+
+    let mut grammar = Grammar::new();
+    let [start, x, y] = grammar.sym();
+    grammar.rule(start).rhs([x]);
+    grammar.set_start(start);
+    let history_id = grammar.rules().next().unwrap().history_id;
+    let ahead = HistoryId::new(grammar.history_graph().len() + 2).unwrap();
+    let node = grammar.add_history_node(HistoryNode::Linked {
+        prev: history_id,
+        node: LinkedHistoryNode::Merged { other: ahead },
+    });
+    grammar.rule(start).history(node).rhs([y]);
+    for _ in 0..2 {
+        grammar.add_history_node(HistoryNode::Linked {
+            prev: history_id,
+            node: LinkedHistoryNode::Weight { weight: 1.0 },
+        });
+    }
+    assert_eq!(
+        grammar.validate_history(),
+        Err(HistoryError::MergedForwardReference {
+            node: node.get(),
+            other: ahead,
+        })
+    );
+
+    let mut grammar = Grammar::new();
+    let [start, x, y] = grammar.sym();
+    grammar.rule(start).rhs([x]);
+    grammar.set_start(start);
+    let history_id = grammar.rules().next().unwrap().history_id;
+    let node = grammar.add_history_node(HistoryNode::Linked {
+        prev: history_id,
+        node: LinkedHistoryNode::Merged { other: history_id },
+    });
+    grammar.rule(start).history(node).rhs([y]);
+    assert_eq!(grammar.validate_history(), Ok(()));
+    let binarized = grammar.binarize();
+    assert_eq!(binarized.validate_history(), Ok(()));
+    let histories = binarized.final_history();
+    assert_eq!(histories.len(), binarized.history_graph().len());
+
+    let mut grammar = Grammar::new();
+    let [start, x] = grammar.sym();
+    let missing = HistoryId::new(100).unwrap();
+    grammar.add_rule(RuleRef {
+        lhs: start,
+        rhs: &[x],
+        history_id: missing,
+    });
+    assert_eq!(
+        grammar.validate_history(),
+        Err(HistoryError::RuleOutOfRange {
+            rule: 0,
+            history_id: missing,
+        })
+    );
+}
+
+#[test]
+fn test_rule_events() {
+    let mut grammar = Grammar::new();
+    let [start, a, x, y, z] = grammar.sym();
+    grammar.rule(start).rhs([x, a]).rule(a).rhs([x, y, z]);
+    grammar.set_start(start);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    for (idx, rule) in binarized.rules().enumerate() {
+        let history = &histories[rule.history_id.get()];
+        let expected =
+            [0, 1, 2].map(|n| history.dots.get(n).map(|dot| dot.event_without_tracing()));
+        assert_eq!(binarized.rule_events(idx), expected);
+        assert!(binarized
+            .rule_events(idx)
+            .iter()
+            .all(|event| event.is_some()));
+    }
+}
+
+#[test]
+fn test_factor_without_rhs_history() {
+    let mut grammar = Grammar::new();
+    let [start, w, x, y, z] = grammar.sym();
+    let history_id = grammar.add_history_node(RootHistoryNode::Rule { lhs: start }.into());
+    grammar.add_rule(RuleRef {
+        lhs: start,
+        rhs: &[w, x, y, z],
+        history_id,
+    });
+    grammar.set_start(start);
+    grammar.factor_rule_tail(0, 1);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    for rule in binarized.rules() {
+        let history = &histories[rule.history_id.get()];
+        assert_eq!(history.dots.len(), 3);
+    }
+}
+
+#[test]
+fn test_inline_single_use_history() {
+    let mut grammar = Grammar::new();
+    let [start, a, x, y, z] = grammar.sym();
+    grammar.rule(start).rhs([x, a]).rule(a).rhs([x, y, z]);
+    grammar.set_start(start);
+    grammar.inline_single_use(&[start]);
+    assert_eq!(grammar.productions_of(start), vec![vec![x, x, y, z]]);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    let traces: Vec<_> = binarized
+        .rules()
+        .map(|rule| {
+            let history = &histories[rule.history_id.get()];
+            history
+                .dots
+                .iter()
+                .map(|dot| dot.trace())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        traces,
+        vec![
+            vec![Some((0, 0)), Some((0, 3)), Some((0, 4))],
+            vec![None, Some((0, 2)), None],
+            vec![None, Some((0, 1)), None],
+        ]
+    );
+}
+
+#[test]
+fn test_merged_history() {
+    let mut grammar = Grammar::new();
+    let [start, a, b, c, d, x, y, z] = grammar.sym();
+    grammar
+        .rule(start)
+        .rhs([a, b, c, x])
+        .rhs_with_linked_history([a, b, d, y, z], LinkedHistoryNode::Weight { weight: 2.0 });
+    grammar.set_start(start);
+
+    let shared_history = |grammar: &Grammar| {
+        let binarized = grammar.binarize();
+        let histories = binarized.final_history();
+        let (idx, shared) = binarized
+            .indexed_rules()
+            .find(|(_, rule)| rule.rhs == [a, b])
+            .unwrap();
+        let history = histories[shared.history_id.get()].clone();
+        let expected =
+            [0, 1, 2].map(|n| history.dots.get(n).map(|dot| dot.event_without_tracing()));
+        assert_eq!(binarized.rule_events(idx), expected);
+        history
+    };
+
+    let first_only = shared_history(&grammar);
+    assert_eq!(first_only.weight, None);
+
+    grammar.set_merge_histories_on_dedup(true);
+    let merged = shared_history(&grammar);
+    let traces: Vec<_> = merged.dots.iter().map(|dot| dot.trace()).collect();
+    assert_eq!(traces, vec![None, Some((0, 1)), None]);
+    assert_eq!(merged.weight, Some(2.0));
+}
+
+#[test]
+fn test_clear_histories_then_binarize() {
+    let mut grammar = Grammar::new();
+    let [start, w, x, y, z] = grammar.sym();
+    grammar.rule(start).rhs([w, x, y, z]);
+    grammar.set_start(start);
+    grammar.clear_histories();
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    let traces: Vec<_> = binarized
+        .rules()
+        .map(|rule| {
+            let history = &histories[rule.history_id.get()];
+            history
+                .dots
+                .iter()
+                .map(|dot| dot.trace())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        traces,
+        vec![
+            vec![Some((0, 0)), Some((0, 3)), Some((0, 4))],
+            vec![None, Some((0, 2)), None],
+            vec![None, Some((0, 1)), None],
+        ]
+    );
+}
+
+#[test]
+fn test_flatten_trivial_chains_history() {
+    let mut grammar = Grammar::new();
+    let [start, b, c, x, y, z] = grammar.sym();
+    grammar
+        .rule(start)
+        .rhs([b])
+        .rule(b)
+        .rhs([c])
+        .rule(c)
+        .rhs([x, y, z]);
+    grammar.set_start(start);
+    grammar.flatten_trivial_chains(&[start]);
+    assert_eq!(grammar.productions_of(start), vec![vec![x, y, z]]);
+    assert_eq!(grammar.rule_count(), 1);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    let traces: Vec<_> = binarized
+        .rules()
+        .map(|rule| {
+            let history = &histories[rule.history_id.get()];
+            history
+                .dots
+                .iter()
+                .map(|dot| dot.trace())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert_eq!(
+        traces,
+        vec![
+            vec![Some((0, 0)), Some((0, 2)), Some((0, 3))],
+            vec![None, Some((0, 1)), None],
+        ]
+    );
+}
+
+#[test]
+fn test_factor_subsequence_history() {
+    let mut grammar = Grammar::new();
+    let [start, w, x, y, z] = grammar.sym();
+    grammar.rule(start).rhs([w, x, y, z]);
+    grammar.set_start(start);
+    let t = grammar.factor_subsequence(&[x, y]);
+    assert_eq!(grammar.productions_of(start), vec![vec![w, t, z]]);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    let traces = |rhs: &[cfg::Symbol]| {
+        let rule = binarized.rules().find(|rule| rule.rhs == rhs).unwrap();
+        let history = &histories[rule.history_id.get()];
+        history
+            .dots
+            .iter()
+            .map(|dot| dot.trace())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(traces(&[w, t]), vec![None, Some((0, 1)), None]);
+    assert_eq!(traces(&[x, y]), vec![None, Some((0, 2)), None]);
+    let top = binarized.rules().find(|rule| rule.lhs == start).unwrap();
+    let history = &histories[top.history_id.get()];
+    assert_eq!(history.dots[1].trace(), Some((0, 3)));
+    assert_eq!(history.dots[2].trace(), Some((0, 4)));
+
+    let mut all_traces = binarized
+        .rules()
+        .flat_map(|rule| histories[rule.history_id.get()].dots.iter())
+        .filter_map(|dot| dot.trace())
+        .collect::<Vec<_>>();
+    all_traces.sort();
+    assert_eq!(all_traces, vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]);
+}
+
+#[test]
+fn test_factor_subsequence_history_repeated() {
+    let mut grammar = Grammar::new();
+    let [start, x, y] = grammar.sym();
+    grammar.rule(start).rhs([x, y, x, y]);
+    grammar.set_start(start);
+    let t = grammar.factor_subsequence(&[x, y]);
+    assert_eq!(grammar.productions_of(start), vec![vec![t, t]]);
+
+    let binarized = grammar.binarize();
+    let histories = binarized.final_history();
+    let trace = |lhs| {
+        let rule = binarized.rules().find(|rule| rule.lhs == lhs).unwrap();
+        let history = &histories[rule.history_id.get()];
+        history
+            .dots
+            .iter()
+            .map(|dot| dot.trace())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(trace(start), vec![Some((0, 0)), Some((0, 2)), Some((0, 4))]);
+    assert_eq!(trace(t), vec![None, Some((0, 1)), None]);
+}
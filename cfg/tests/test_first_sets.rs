@@ -1,7 +1,7 @@
 #![cfg(feature = "cfg-predict")]
 
 use cfg::{Cfg, RuleContainer};
-use cfg_predict::{FirstSets, PredictSets};
+use cfg_predict::{FirstSets, FollowSets, PredictSets, PredictTerminal::*};
 
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -26,17 +26,17 @@ fn test_simple_first_sets() {
 
     let mut map = BTreeMap::new();
     let mut start_set = BTreeSet::new();
-    start_set.insert(Some(x));
-    start_set.insert(Some(y));
+    start_set.insert(Terminal(x));
+    start_set.insert(Terminal(y));
     let mut a_set = BTreeSet::new();
-    a_set.insert(None);
+    a_set.insert(Epsilon);
     let mut b_set = BTreeSet::new();
-    b_set.insert(None);
-    b_set.insert(Some(x));
-    b_set.insert(Some(y));
+    b_set.insert(Epsilon);
+    b_set.insert(Terminal(x));
+    b_set.insert(Terminal(y));
     let mut c_set = BTreeSet::new();
-    c_set.insert(Some(x));
-    c_set.insert(Some(y));
+    c_set.insert(Terminal(x));
+    c_set.insert(Terminal(y));
 
     map.insert(start, start_set);
     map.insert(a, a_set);
@@ -66,16 +66,16 @@ fn test_simple_first_sets_altered() {
 
     let mut map = BTreeMap::new();
     let mut start_set = BTreeSet::new();
-    start_set.insert(Some(x));
+    start_set.insert(Terminal(x));
     let mut a_set = BTreeSet::new();
-    a_set.insert(None);
+    a_set.insert(Epsilon);
     let mut b_set = BTreeSet::new();
-    b_set.insert(None);
-    b_set.insert(Some(x));
-    b_set.insert(Some(y));
+    b_set.insert(Epsilon);
+    b_set.insert(Terminal(x));
+    b_set.insert(Terminal(y));
     let mut c_set = BTreeSet::new();
-    c_set.insert(Some(x));
-    c_set.insert(Some(y));
+    c_set.insert(Terminal(x));
+    c_set.insert(Terminal(y));
 
     map.insert(start, start_set);
     map.insert(a, a_set);
@@ -84,3 +84,30 @@ fn test_simple_first_sets_altered() {
 
     assert_eq!(sets, &map);
 }
+
+#[test]
+fn test_epsilon_and_end_of_input() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b])
+        .rule(a)
+        .rhs([x])
+        .rhs([])
+        .rule(b)
+        .rhs([a, y]);
+    let first_sets = FirstSets::new(&cfg);
+    let follow_sets = FollowSets::new(&cfg, start, first_sets.predict_sets());
+    let first = first_sets.predict_sets();
+    let follow = follow_sets.predict_sets();
+
+    assert_eq!(first[&a], BTreeSet::from([Terminal(x), Epsilon]));
+    assert!(!first[&b].contains(&Epsilon));
+    assert_eq!(follow[&start], BTreeSet::from([EndOfInput]));
+    assert_eq!(follow[&b], BTreeSet::from([EndOfInput]));
+    assert_eq!(follow[&a], BTreeSet::from([Terminal(x), Terminal(y)]));
+    for set in follow.values() {
+        assert!(!set.contains(&Epsilon));
+    }
+}
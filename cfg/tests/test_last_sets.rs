@@ -1,7 +1,7 @@
 #![cfg(feature = "cfg-predict")]
 
 use cfg::{Cfg, RuleContainer};
-use cfg_predict::{LastSets, PredictSets};
+use cfg_predict::{LastSets, PredictSets, PredictTerminal::*};
 
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -26,17 +26,17 @@ fn test_simple_last_sets() {
 
     let mut map = BTreeMap::new();
     let mut start_set = BTreeSet::new();
-    start_set.insert(Some(x));
-    start_set.insert(Some(y));
+    start_set.insert(Terminal(x));
+    start_set.insert(Terminal(y));
     let mut a_set = BTreeSet::new();
-    a_set.insert(None);
+    a_set.insert(Epsilon);
     let mut b_set = BTreeSet::new();
-    b_set.insert(None);
-    b_set.insert(Some(x));
-    b_set.insert(Some(y));
+    b_set.insert(Epsilon);
+    b_set.insert(Terminal(x));
+    b_set.insert(Terminal(y));
     let mut c_set = BTreeSet::new();
-    c_set.insert(Some(x));
-    c_set.insert(Some(y));
+    c_set.insert(Terminal(x));
+    c_set.insert(Terminal(y));
 
     map.insert(start, start_set);
     map.insert(a, a_set);
@@ -66,17 +66,17 @@ fn test_simple_last_sets_altered() {
 
     let mut map = BTreeMap::new();
     let mut start_set = BTreeSet::new();
-    start_set.insert(Some(x));
-    start_set.insert(Some(y));
+    start_set.insert(Terminal(x));
+    start_set.insert(Terminal(y));
     let mut a_set = BTreeSet::new();
-    a_set.insert(None);
+    a_set.insert(Epsilon);
     let mut b_set = BTreeSet::new();
-    b_set.insert(None);
-    b_set.insert(Some(x));
-    b_set.insert(Some(y));
+    b_set.insert(Epsilon);
+    b_set.insert(Terminal(x));
+    b_set.insert(Terminal(y));
     let mut c_set = BTreeSet::new();
-    c_set.insert(Some(x));
-    c_set.insert(Some(y));
+    c_set.insert(Terminal(x));
+    c_set.insert(Terminal(y));
 
     map.insert(start, start_set);
     map.insert(a, a_set);
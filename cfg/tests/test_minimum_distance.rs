@@ -64,3 +64,31 @@ fn test_minimum_distance() {
     //     assert_eq!(result, expected);
     // }
 }
+
+#[test]
+fn test_minimum_distance_reset() {
+    let mut cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+    cfg.rule(start)
+        .rhs_with_linked_history([a, x, b], distances(&[1, 2]))
+        .rhs_with_linked_history([b, y], distances(&[0]))
+        .rule(a)
+        .rhs_with_linked_history([x, x], empty())
+        .rhs_with_linked_history([], empty())
+        .rule(b)
+        .rhs_with_linked_history([y, a], empty());
+
+    let mut minimal_distance = MinimalDistance::new(&cfg);
+    let first = minimal_distance.minimal_distances().to_vec();
+
+    minimal_distance.reset();
+    assert!(minimal_distance
+        .distances()
+        .iter()
+        .all(|(_, rule_distances)| rule_distances.iter().all(Option::is_none)));
+
+    let second = minimal_distance.minimal_distances().to_vec();
+    let fresh = MinimalDistance::new(&cfg).minimal_distances().to_vec();
+    assert_eq!(first, fresh);
+    assert_eq!(second, fresh);
+}
@@ -3,6 +3,7 @@
 mod support;
 
 use cfg::precedenced_rule::Associativity::*;
+use cfg::precedenced_rule::PrecedenceError;
 use cfg::{Cfg, RuleContainer};
 use cfg_classify::useful::Usefulness;
 
@@ -45,6 +46,8 @@ fn test_simple_precedence() {
         .rhs([var, eq, top])
         .finalize();
 
+    assert_eq!(cfg.validate_precedence(), Ok(()));
+
     let mut equivalent: Cfg = Cfg::new();
     let [start, top, num, var] = equivalent.sym();
     let [l_paren, r_paren, exp, mul, div, plus, minus, eq] = equivalent.sym();
@@ -119,3 +122,58 @@ fn test_ternary_quaternary() {
     support::assert_eq_rules(equivalent.rules(), cfg.rules());
     assert!(Usefulness::new(&mut cfg).reachable([start]).all_useful());
 }
+
+#[test]
+fn test_precedence_levels() {
+    let mut cfg: Cfg = Cfg::new();
+    let [top, num, mul, plus] = cfg.sym();
+
+    cfg.precedenced_rule(top)
+        .rhs([num])
+        .rhs([top, mul, top])
+        .lower_precedence()
+        .rhs([top, plus, top])
+        .finalize();
+
+    let levels = cfg.precedence_levels();
+    let level_rhs = |level: u32| -> Vec<Vec<_>> {
+        levels[&level]
+            .iter()
+            .map(|&idx| cfg.rule_at(idx).unwrap().rhs.to_vec())
+            .collect()
+    };
+    assert_eq!(levels.len(), 2);
+    assert_eq!(level_rhs(0).len(), 2);
+    assert_eq!(level_rhs(0)[0], vec![num]);
+    assert_eq!(level_rhs(1).len(), 1);
+    assert_eq!(level_rhs(1)[0][1], plus);
+}
+
+#[test]
+fn test_validate_precedence() {
+    let mut cfg: Cfg = Cfg::new();
+    let [top, num, mul, plus] = cfg.sym();
+
+    cfg.precedenced_rule(top)
+        .rhs([num])
+        .rhs([top, mul, top])
+        .lower_precedence()
+        .rhs([top, plus, top])
+        .lower_precedence()
+        .lower_precedence()
+        .rhs([top, plus, num])
+        .finalize();
+
+    assert_eq!(cfg.validate_precedence(), Ok(()));
+
+    // Remove the rule that links the loosest level to the level of `+`.
+    let loosest = cfg.rule_at(cfg.precedence_levels()[&3][0]).unwrap().lhs;
+    cfg.drain_rules_where(|rule| rule.lhs == loosest && rule.rhs.len() == 1);
+    assert_eq!(
+        cfg.validate_precedence(),
+        Err(PrecedenceError::MissingTighterLevel {
+            lhs: loosest,
+            looseness: 3,
+        })
+    );
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "cfg-predict")]
+
+use cfg::{Cfg, RuleContainer};
+use cfg_predict::{FirstSets, FollowSets, PredictCache, PredictSets, PredictTerminal::*};
+
+use std::collections::BTreeSet;
+
+#[test]
+fn test_predict_cache_invalidation() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([y]);
+
+    let mut cache = PredictCache::new(start);
+    let a_follow: BTreeSet<_> = [Terminal(x)].into_iter().collect();
+    assert_eq!(cache.follow_sets(&cfg)[&a], a_follow);
+
+    cfg.rule(start).rhs([a, y]);
+
+    let a_follow: BTreeSet<_> = [Terminal(x), Terminal(y)].into_iter().collect();
+    assert_eq!(cache.follow_sets(&cfg)[&a], a_follow);
+    let start_first: BTreeSet<_> = [Terminal(y)].into_iter().collect();
+    assert_eq!(cache.first_sets(&cfg)[&start], start_first);
+}
+
+#[test]
+fn test_predict_cache_kept_without_changes() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rhs([a, y]).rule(a).rhs([y]);
+
+    let revision = cfg.revision();
+    cfg.retain(|_| true);
+    cfg.retain_map(|_| true);
+    cfg.sort_alternatives();
+    cfg.remove_duplicates();
+    assert_eq!(cfg.revision(), revision);
+
+    cfg.retain_map(|rule| {
+        if rule.lhs == a {
+            rule.rhs = vec![x];
+        }
+        true
+    });
+    assert!(cfg.revision() > revision);
+    let revision = cfg.revision();
+    cfg.retain(|rule| rule.rhs != [a, y]);
+    assert!(cfg.revision() > revision);
+}
+
+#[test]
+fn test_predict_cache_follow_of() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b, x])
+        .rule(a)
+        .rhs([y])
+        .rule(b)
+        .rhs([y])
+        .rhs([]);
+
+    let first_sets = FirstSets::new(&cfg);
+    let follow_sets = FollowSets::new(&cfg, start, first_sets.predict_sets());
+    let mut cache = PredictCache::new(start);
+    for sym in [start, a, b] {
+        assert_eq!(cache.follow_of(&cfg, sym), follow_sets.predict_sets()[&sym]);
+    }
+    let a_follow: BTreeSet<_> = [Terminal(x), Terminal(y)].into_iter().collect();
+    assert_eq!(cache.follow_of(&cfg, a), a_follow);
+    assert!(cache.follow_of(&cfg, x).is_empty());
+}
+
+#[test]
+fn test_predict_cache_slr_reductions() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b, x])
+        .rule(a)
+        .rhs([y])
+        .rule(b)
+        .rhs([y])
+        .rhs([]);
+
+    let mut cache = PredictCache::new(start);
+    let reductions = cache.slr_reductions(&cfg);
+    assert_eq!(reductions.len(), cfg.rule_count());
+    for (idx, lookahead) in reductions {
+        let lhs = cfg.rule_at(idx).unwrap().lhs;
+        assert_eq!(lookahead, cache.follow_of(&cfg, lhs));
+    }
+    let b_follow: BTreeSet<_> = [Terminal(x)].into_iter().collect();
+    assert_eq!(cache.slr_reductions(&cfg)[3], (3, b_follow));
+}
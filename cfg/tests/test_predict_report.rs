@@ -0,0 +1,34 @@
+#![cfg(feature = "cfg-predict")]
+
+use cfg::{Cfg, RuleContainer};
+use cfg_predict::{FirstSets, FollowSets, PredictReport, PredictSets};
+
+#[test]
+fn test_predict_report() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b, x])
+        .rule(a)
+        .rhs([y])
+        .rhs([])
+        .rule(b)
+        .rhs([a, y]);
+
+    let report = PredictReport::new(&cfg, start);
+    let first_sets = FirstSets::new(&cfg);
+    let follow_sets = FollowSets::new(&cfg, start, first_sets.predict_sets());
+
+    assert_eq!(
+        report.entries.keys().cloned().collect::<Vec<_>>(),
+        vec![start, a, b]
+    );
+    for (nonterminal, entry) in &report.entries {
+        assert_eq!(entry.first, first_sets.predict_sets()[nonterminal]);
+        assert_eq!(entry.follow, follow_sets.predict_sets()[nonterminal]);
+    }
+    assert!(report.entries[&a].nullable);
+    assert!(!report.entries[&b].nullable);
+    assert!(!report.entries[&start].nullable);
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "cfg-classify")]
+
+use cfg::classify::recursive::{Recursion, RecursionKind, RecursionSummary};
+use cfg::{Cfg, RuleContainer};
+
+#[test]
+fn test_recursion_summary() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, left, right, middle, all, a, b] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([left, right, middle, all])
+        .rule(left)
+        .rhs([left, a])
+        .rhs([a])
+        .rule(right)
+        .rhs([a, right])
+        .rhs([a])
+        .rule(middle)
+        .rhs([a, middle, b])
+        .rhs([b])
+        .rule(all)
+        .rhs([all, a, all])
+        .rhs([b]);
+
+    let recursion = Recursion::new(&cfg);
+    let kinds: Vec<_> = recursion
+        .recursive_rules()
+        .map(|recursive| (recursive.rule.lhs, recursive.recursion))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (left, RecursionKind::Left),
+            (right, RecursionKind::Right),
+            (middle, RecursionKind::Middle),
+            (all, RecursionKind::All),
+        ]
+    );
+    assert_eq!(
+        recursion.summary(),
+        RecursionSummary {
+            left: 1,
+            right: 1,
+            middle: 1,
+            all: 1,
+        }
+    );
+}
+
+#[test]
+fn test_hidden_left_recursion() {
+    let mut cfg: Cfg = Cfg::new();
+    let [a, b, x] = cfg.sym();
+
+    cfg.rule(a)
+        .rhs([b, a])
+        .rhs([b, a, x])
+        .rhs([x])
+        .rule(b)
+        .rhs([]);
+
+    let recursion = Recursion::new(&cfg);
+    let kinds: Vec<_> = recursion
+        .recursive_rules()
+        .map(|recursive| recursive.recursion)
+        .collect();
+
+    assert_eq!(kinds, vec![RecursionKind::All, RecursionKind::Left]);
+}
+
+#[test]
+fn test_max_right_recursion_chain() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+    let chain: [_; 10] = cfg.sym();
+
+    cfg.rule(start).rhs([chain[0]]);
+    for (i, &lhs) in chain.iter().enumerate() {
+        let next = chain[(i + 1) % chain.len()];
+        cfg.rule(lhs).rhs([x, next]).rhs([y]);
+    }
+    // A shorter, separate chain.
+    let [a, b] = cfg.sym();
+    cfg.rule(a).rhs([x, b]).rule(b).rhs([y, a]).rhs([y]);
+
+    assert_eq!(Recursion::new(&cfg).max_right_recursion_chain(), Some(10));
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x] = cfg.sym();
+    cfg.rule(start).rhs([start, x]).rhs([x]);
+
+    assert_eq!(Recursion::new(&cfg).max_right_recursion_chain(), None);
+}
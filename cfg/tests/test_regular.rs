@@ -0,0 +1,81 @@
+#![cfg(feature = "cfg-classify")]
+
+use cfg::classify::regular::{nonterminal_to_regex, Dfa, NotRegularError};
+use cfg::{Cfg, RuleContainer};
+
+#[test]
+fn test_dfa() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, b_then_start, a, b] = cfg.sym();
+
+    // start ::= a b start | ()
+    cfg.rule(start)
+        .rhs([a, b_then_start])
+        .rhs([])
+        .rule(b_then_start)
+        .rhs([b, start]);
+
+    let dfa = Dfa::new(&cfg, start).unwrap();
+
+    assert!(dfa.accepts(&[]));
+    assert!(dfa.accepts(&[a, b]));
+    assert!(dfa.accepts(&[a, b, a, b]));
+    assert!(!dfa.accepts(&[a, b, a]));
+    assert!(!dfa.accepts(&[b, a]));
+    assert_eq!(dfa.states().len(), 2);
+}
+
+#[test]
+fn test_dfa_not_regular() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b] = cfg.sym();
+
+    // start ::= a start b | ()
+    cfg.rule(start).rhs([a, start, b]).rhs([]);
+
+    let expected = NotRegularError {
+        lhs: start,
+        rhs: vec![a, start, b],
+    };
+    assert_eq!(Dfa::new(&cfg, start), Err(expected));
+}
+
+#[test]
+fn test_nonterminal_to_regex() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, list, b_then_list, a, b, x] =
+        cfg.named_syms(["start", "list", "b_then_list", "a", "b", "x"]);
+
+    // start ::= x list x
+    // list ::= a b list | ()
+    cfg.rule(start)
+        .rhs([x, list, x])
+        .rule(list)
+        .rhs([a, b_then_list])
+        .rhs([])
+        .rule(b_then_list)
+        .rhs([b, list]);
+
+    let regex = nonterminal_to_regex(&cfg, list);
+    assert_eq!(regex.as_deref(), Some("(a b)*"));
+    assert_eq!(nonterminal_to_regex(&cfg, start), None);
+}
+
+#[test]
+fn test_nonterminal_to_regex_optional() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, tail, a, b, c] = cfg.named_syms(["start", "tail", "a", "b", "c"]);
+
+    // start ::= a tail | c
+    // tail ::= b | ()
+    cfg.rule(start)
+        .rhs([a, tail])
+        .rhs([c])
+        .rule(tail)
+        .rhs([b])
+        .rhs([]);
+
+    let regex = nonterminal_to_regex(&cfg, start).unwrap();
+    assert!(regex == "a b? | c" || regex == "c | a b?", "{}", regex);
+    assert_eq!(nonterminal_to_regex(&cfg, tail).as_deref(), Some("b?"));
+}
@@ -138,3 +138,39 @@ fn test_maps() {
         ]
     );
 }
+
+#[test]
+fn test_remove_useless_rules_and_compact() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, unreachable, unproductive, x, y, z] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rhs([unproductive, y])
+        .rule(a)
+        .rhs([y])
+        .rule(unreachable)
+        .rhs([z])
+        .rule(unproductive)
+        .rhs([unproductive, x]);
+
+    let mapping = Usefulness::new(&mut cfg)
+        .reachable([start])
+        .remove_useless_rules_and_compact();
+
+    assert_eq!(cfg.num_syms(), 4);
+    assert_eq!(mapping.len(), 4);
+    assert!(!mapping.contains_key(&unreachable));
+    assert!(!mapping.contains_key(&unproductive));
+    assert!(!mapping.contains_key(&z));
+
+    {
+        let mut equivalent: Cfg = Cfg::new();
+        let [start, a, x, y] = [mapping[&start], mapping[&a], mapping[&x], mapping[&y]];
+        equivalent.rule(start).rhs([a, x]).rule(a).rhs([y]);
+        support::assert_eq_rules(equivalent.rules(), cfg.rules());
+    };
+
+    let start = mapping[&start];
+    assert!(Usefulness::new(&mut cfg).reachable([start]).all_useful());
+}
@@ -0,0 +1,1157 @@
+mod support;
+
+use cfg::cfg::{EarleyReadyError, RhsKind};
+use cfg::rhs_closure::{RhsClosure, RhsPropertyMode};
+use cfg::symbol::set::SymbolBitSet;
+use cfg::{AsRuleRef, Cfg, RuleContainer};
+
+#[test]
+fn test_rule_kinds() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b])
+        .rule(a)
+        .rhs([x, b])
+        .rule(b)
+        .rhs([x, y])
+        .rhs([]);
+
+    let rules: Vec<_> = cfg.rules().collect();
+    let kinds: Vec<_> = rules
+        .iter()
+        .map(|rule| {
+            (
+                cfg.rule_is_purely_terminal(rule),
+                cfg.rule_is_purely_nonterminal(rule),
+                cfg.rule_is_mixed(rule),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (false, true, false),
+            (false, false, true),
+            (true, false, false),
+            (false, false, false),
+        ]
+    );
+    assert_eq!(
+        cfg.rhs_kinds(),
+        vec![
+            RhsKind::PurelyNonterminal,
+            RhsKind::Mixed,
+            RhsKind::PurelyTerminal,
+            RhsKind::Empty,
+        ]
+    );
+}
+
+#[test]
+fn test_dotted_items() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b])
+        .rule(a)
+        .rhs([x, b, x])
+        .rule(b)
+        .rhs([]);
+
+    let expected: usize = cfg.rules().map(|rule| rule.rhs.len() + 1).sum();
+    assert_eq!(cfg.dotted_items().count(), expected);
+    assert_eq!(expected, 8);
+
+    let items: Vec<_> = cfg
+        .dotted_items()
+        .map(|(rule_idx, dot, _)| (rule_idx, dot))
+        .collect();
+    assert_eq!(
+        items,
+        vec![
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 0),
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 0)
+        ]
+    );
+}
+
+#[test]
+fn test_sort_alternatives() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([y, x])
+        .rule(a)
+        .rhs([y])
+        .rhs([x])
+        .rule(start)
+        .rhs([x, y])
+        .rhs([x]);
+
+    cfg.sort_alternatives();
+
+    let rules: Vec<_> = cfg
+        .rules()
+        .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+        .collect();
+    assert_eq!(
+        rules,
+        vec![
+            (start, vec![x]),
+            (a, vec![x]),
+            (a, vec![y]),
+            (start, vec![x, y]),
+            (start, vec![y, x]),
+        ]
+    );
+}
+
+#[test]
+fn test_remove_duplicates() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rule(a)
+        .rhs([y])
+        .rule(start)
+        .rhs([a, x])
+        .rhs([x])
+        .rule(a)
+        .rhs([y])
+        .rule(start)
+        .rhs([x]);
+
+    cfg.remove_duplicates();
+
+    let rules: Vec<_> = cfg
+        .rules()
+        .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+        .collect();
+    assert_eq!(
+        rules,
+        vec![(start, vec![a, x]), (a, vec![y]), (start, vec![x])]
+    );
+}
+
+#[test]
+fn test_unique_rule_names() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x, a, y])
+        .rhs([a, x, a, y])
+        .rule(a)
+        .rhs([x])
+        .rhs([]);
+
+    cfg.ensure_unique_rule_names();
+
+    let labels: Vec<_> = cfg
+        .rules()
+        .map(|rule| cfg.history_graph().label(rule.history_id))
+        .collect();
+    assert_eq!(labels, vec![Some(0), Some(1), Some(2), Some(3)]);
+
+    let binarized = cfg.binarize();
+    let mut binarized_labels: Vec<_> = binarized
+        .rules()
+        .map(|rule| binarized.history_graph().label(rule.history_id))
+        .collect();
+    binarized_labels.sort();
+    binarized_labels.dedup();
+    assert_eq!(binarized_labels, labels);
+}
+
+#[test]
+fn test_closure_reaches() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, c, unproductive, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b])
+        .rule(a)
+        .rhs([x])
+        .rule(b)
+        .rhs([a, unproductive])
+        .rhs([y])
+        .rule(c)
+        .rhs([unproductive, x])
+        .rule(unproductive)
+        .rhs([unproductive, y]);
+
+    let seed = SymbolBitSet::terminal_set(&cfg);
+    for mode in [RhsPropertyMode::All, RhsPropertyMode::Any] {
+        let mut closure = seed.clone().into_bit_vec();
+        match mode {
+            RhsPropertyMode::All => RhsClosure::new(&cfg).rhs_closure(&mut closure),
+            RhsPropertyMode::Any => RhsClosure::new(&cfg).rhs_closure_for_any(&mut closure),
+        }
+        for target in [start, a, b, c, unproductive, x] {
+            assert_eq!(
+                cfg.closure_reaches(&seed, target, mode),
+                closure[target.usize()]
+            );
+        }
+    }
+    assert!(!cfg.closure_reaches(&seed, c, RhsPropertyMode::All));
+    assert!(cfg.closure_reaches(&seed, c, RhsPropertyMode::Any));
+
+    // A rule with an empty RHS gives its LHS the property in the `All` mode.
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a] = cfg.sym();
+    cfg.rule(start).rhs([]).rule(a).rhs([start, start]);
+    let seed = SymbolBitSet::new(&cfg, false);
+    let mut closure = seed.clone().into_bit_vec();
+    RhsClosure::new(&cfg).rhs_closure(&mut closure);
+    let mut witnessed = seed.clone();
+    let witness = cfg.rhs_closure_with_witness(&mut witnessed, RhsPropertyMode::All);
+    for target in [start, a] {
+        assert!(closure[target.usize()]);
+        assert!(witnessed.has_sym(target));
+        assert!(cfg.closure_reaches(&seed, target, RhsPropertyMode::All));
+        assert!(!cfg.closure_reaches(&seed, target, RhsPropertyMode::Any));
+    }
+    assert_eq!(witness[&start], 0);
+    assert_eq!(witness[&a], 1);
+}
+
+#[test]
+fn test_find_ambiguity() {
+    let mut cfg: Cfg = Cfg::new();
+    let [expr, a] = cfg.sym();
+
+    cfg.rule(expr).rhs([expr, expr]).rhs([a]);
+
+    assert_eq!(cfg.find_ambiguity(expr, 2), None);
+    assert_eq!(cfg.find_ambiguity(expr, 3), Some(vec![a, a, a]));
+}
+
+#[test]
+fn test_find_ambiguity_unambiguous() {
+    let mut cfg: Cfg = Cfg::new();
+    let [list, item, unit, a, b] = cfg.sym();
+
+    cfg.rule(list)
+        .rhs([item, list])
+        .rhs([])
+        .rule(item)
+        .rhs([a])
+        .rhs([b, unit])
+        .rule(unit)
+        .rhs([unit, a]);
+
+    assert_eq!(cfg.find_ambiguity(list, 5), None);
+
+    cfg.rule(list).rhs([list]);
+    assert_eq!(cfg.find_ambiguity(list, 5), Some(vec![]));
+}
+
+#[test]
+fn test_rule_at() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rhs([y])
+        .rule(a)
+        .rhs([])
+        .rhs([x, y]);
+
+    assert_eq!(cfg.rule_count(), 4);
+    for i in 0..=cfg.rule_count() {
+        let rule_at = cfg
+            .rule_at(i)
+            .map(|rule| (rule.lhs, rule.rhs, rule.history_id));
+        let nth = cfg
+            .rules()
+            .nth(i)
+            .map(|rule| (rule.lhs, rule.rhs, rule.history_id));
+        assert_eq!(rule_at, nth);
+    }
+    assert!(cfg.rule_at(4).is_none());
+}
+
+#[test]
+fn test_equivalent_rules() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rhs([y])
+        .rule(a)
+        .rhs([a, x])
+        .rule(start)
+        .rhs([a, x]);
+
+    assert_eq!(cfg.rule_signature(2), (a, vec![a, x].into()));
+    assert_eq!(cfg.equivalent_rules(), vec![vec![0, 3], vec![1], vec![2]]);
+}
+
+#[test]
+fn test_named_syms() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x] = cfg.named_syms(["start", "x"]);
+    let [unnamed] = cfg.sym();
+
+    cfg.rule(start).rhs([x, unnamed]);
+
+    assert_eq!(cfg.num_syms(), 3);
+    assert_eq!(cfg.sym_source().name_of(start), Some("start"));
+    assert_eq!(cfg.sym_source().name_of(x), Some("x"));
+    assert_eq!(cfg.sym_source().name_of(unnamed), None);
+
+    cfg.sym_source_mut().set_name(unnamed, "x");
+    let names = cfg.sym_source().name_to_symbol();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names["start"], start);
+    assert_eq!(names["x"], x);
+    assert_eq!(cfg.sym_source().find_by_name("x"), Some(x));
+}
+
+#[test]
+fn test_productions_of() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rule(a)
+        .rhs([x])
+        .rule(start)
+        .rhs([y])
+        .rhs([]);
+
+    assert_eq!(cfg.productions_of(start), vec![vec![a, x], vec![y], vec![]]);
+    assert_eq!(cfg.productions_of(x), Vec::<Vec<_>>::new());
+}
+
+#[test]
+fn test_rules_common_prefix() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, w, x, y, z] = cfg.sym();
+
+    cfg.rule(a)
+        .rhs([x, y, z])
+        .rhs([x, y, w])
+        .rule(start)
+        .rhs([a]);
+
+    assert_eq!(cfg.rules_common_prefix(a), 2);
+    assert_eq!(cfg.rules_common_prefix(start), 1);
+    assert_eq!(cfg.rules_common_prefix(x), 0);
+    let rules: Vec<_> = cfg.rules().collect();
+    assert_eq!(cfg.common_prefix(rules[0], rules[1]), 2);
+    assert_eq!(cfg.common_prefix(rules[0], rules[2]), 0);
+}
+
+#[test]
+fn test_rhs_symbols_of() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x, x])
+        .rhs([x, y])
+        .rhs([])
+        .rule(a)
+        .rhs([y]);
+
+    assert_eq!(
+        cfg.rhs_symbols_of(start).into_iter().collect::<Vec<_>>(),
+        vec![a, x, y]
+    );
+    assert!(cfg.rhs_symbols_of(x).is_empty());
+}
+
+#[test]
+fn test_terminal_frequencies() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x, x])
+        .rhs([x])
+        .rule(a)
+        .rhs([x, y])
+        .rhs([y, x]);
+
+    let frequencies = cfg.terminal_frequencies();
+    assert_eq!(frequencies.get(&x), Some(&5));
+    assert_eq!(frequencies.get(&y), Some(&2));
+    assert_eq!(frequencies.get(&a), None);
+    assert_eq!(frequencies.len(), 2);
+}
+
+#[test]
+fn test_rule_str() {
+    let mut cfg: Cfg = Cfg::new();
+    cfg.rule_str("expr")
+        .rhs_str(&["expr", "plus", "term"])
+        .rhs_str(&["term"])
+        .rule_str("term")
+        .rhs_str(&["number"]);
+
+    let mut equivalent: Cfg = Cfg::new();
+    let [expr, plus, term, number] = equivalent.sym();
+    equivalent
+        .rule(expr)
+        .rhs([expr, plus, term])
+        .rhs([term])
+        .rule(term)
+        .rhs([number]);
+
+    assert_eq!(cfg.num_syms(), 4);
+    let rules = |cfg: &Cfg| -> Vec<_> {
+        cfg.rules()
+            .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+            .collect()
+    };
+    assert_eq!(rules(&cfg), rules(&equivalent));
+    assert_eq!(cfg.sym_source().find_by_name("term"), Some(term));
+}
+
+#[test]
+fn test_shortest_derivations() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, expr, term, unproductive, plus, num, l_paren, r_paren] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([expr])
+        .rhs([unproductive])
+        .rule(expr)
+        .rhs([expr, plus, term])
+        .rhs([term])
+        .rule(term)
+        .rhs([l_paren, expr, r_paren])
+        .rhs([num, num])
+        .rule(unproductive)
+        .rhs([unproductive, num]);
+
+    let shortest = cfg.shortest_derivations();
+    assert_eq!(shortest[&start], Some(vec![num, num]));
+    assert_eq!(shortest[&expr], Some(vec![num, num]));
+    assert_eq!(shortest[&unproductive], None);
+    assert_eq!(shortest[&plus], Some(vec![plus]));
+    assert_eq!(shortest.len(), cfg.num_syms());
+}
+
+#[test]
+fn test_factor_rule_tail() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y, z, w] = cfg.sym();
+
+    cfg.rule(start).rhs([x, y, z, w]).rhs([x]);
+
+    let tail_sym = cfg.factor_rule_tail(0, 2);
+
+    assert_eq!(cfg.rule_count(), 3);
+    assert_eq!(
+        cfg.productions_of(start),
+        vec![vec![x, y, tail_sym], vec![x]]
+    );
+    assert_eq!(cfg.productions_of(tail_sym), vec![vec![z, w]]);
+
+    let head = cfg.rule_at(0).unwrap();
+    let tail = cfg.rule_at(2).unwrap();
+    let mut reconstructed = head.rhs[..head.rhs.len() - 1].to_vec();
+    reconstructed.extend(tail.rhs.iter().cloned());
+    assert_eq!(reconstructed, vec![x, y, z, w]);
+}
+
+#[test]
+fn test_debug_assert_binary() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([y]);
+
+    cfg.debug_assert_binary();
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "rule 1 is not binary")]
+fn test_debug_assert_binary_long_rule() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y, z] = cfg.sym();
+
+    cfg.rule(start).rhs([x, y]).rhs([x, y, z]);
+
+    cfg.debug_assert_binary();
+}
+
+#[test]
+fn test_remove_rule() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rhs([y]).rule(a).rhs([x, y]);
+
+    let rules = |cfg: &Cfg| -> Vec<_> {
+        cfg.rules()
+            .map(|rule| (rule.lhs, rule.rhs.to_vec(), rule.history_id))
+            .collect()
+    };
+    let before = rules(&cfg);
+
+    let removed = cfg.remove_rule(2).unwrap();
+    assert_eq!(cfg.rule_count(), 2);
+    assert!(cfg.remove_rule(2).is_none());
+    cfg.add_rule(removed.as_rule_ref());
+    assert_eq!(rules(&cfg), before);
+
+    let drained = cfg.drain_rules_where(|rule| rule.lhs == start);
+    assert_eq!(drained.len(), 2);
+    assert_eq!(drained[1].rhs, vec![y]);
+    assert_eq!(rules(&cfg), before[2..].to_vec());
+}
+
+#[test]
+fn test_derivation_depths() {
+    let mut cfg: Cfg = Cfg::new();
+    let [root, a, b, c, x, unreachable] = cfg.sym();
+
+    cfg.rule(root)
+        .rhs([a, b])
+        .rule(a)
+        .rhs([c, root])
+        .rule(b)
+        .rhs([c])
+        .rule(c)
+        .rhs([x])
+        .rule(unreachable)
+        .rhs([root]);
+
+    let depths = cfg.derivation_depths(root);
+    let expected = [(root, 0), (a, 1), (b, 1), (c, 2), (x, 3)];
+    assert_eq!(depths, expected.into_iter().collect());
+    assert!(!depths.contains_key(&unreachable));
+}
+
+#[test]
+fn test_inline_single_use() {
+    let mut cfg: Cfg = Cfg::new();
+    let [root, gensym, recursive, shared, self_loop, x, y] = cfg.sym();
+
+    cfg.rule(self_loop)
+        .rhs([self_loop, y])
+        .rule(root)
+        .rhs([x, gensym, y])
+        .rhs([recursive, shared, shared])
+        .rule(gensym)
+        .rhs([y, y])
+        .rule(recursive)
+        .rhs([recursive, x])
+        .rule(shared)
+        .rhs([x]);
+
+    cfg.inline_single_use(&[root]);
+
+    assert_eq!(cfg.rule_count(), 5);
+    assert_eq!(cfg.productions_of(self_loop), vec![vec![self_loop, y]]);
+    assert_eq!(
+        cfg.productions_of(root),
+        vec![vec![x, y, y, y], vec![recursive, shared, shared]]
+    );
+    assert!(cfg.productions_of(gensym).is_empty());
+    assert_eq!(cfg.productions_of(recursive), vec![vec![recursive, x]]);
+    assert_eq!(cfg.productions_of(shared), vec![vec![x]]);
+}
+
+#[test]
+fn test_symbol_bit_set_count() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y, z] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([y, z]);
+
+    let terminals = SymbolBitSet::terminal_set(&cfg);
+    assert_eq!(terminals.count(), 3);
+    assert_eq!(terminals.count(), terminals.iter().count());
+    assert!(!terminals.is_empty());
+
+    let mut empty = SymbolBitSet::new(&cfg, false);
+    assert_eq!(empty.count(), 0);
+    assert!(empty.is_empty());
+    empty.set(start, true);
+    assert_eq!(empty.count(), 1);
+}
+
+#[test]
+fn test_derivation_closure() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rule(a)
+        .rhs([b])
+        .rule(b)
+        .rhs([y]);
+
+    let closure = cfg.derivation_closure();
+    let derives = |lhs: cfg::Symbol, sym: cfg::Symbol| closure[(lhs.usize(), sym.usize())];
+    assert!(derives(start, a));
+    assert!(derives(start, b));
+    assert!(derives(start, y));
+    assert!(derives(a, y));
+    assert!(derives(x, x));
+    assert!(!derives(a, x));
+    assert!(!derives(b, start));
+    assert!(!derives(y, b));
+}
+
+#[test]
+fn test_minimal_roots() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rule(a)
+        .rhs([x])
+        .rule(b)
+        .rhs([b, x])
+        .rhs([]);
+
+    assert_eq!(cfg.minimal_roots(&[start, a]), vec![start]);
+    assert_eq!(cfg.minimal_roots(&[a, start, b]), vec![start, b]);
+}
+
+#[test]
+fn test_check_earley_ready() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([x]).rhs([y]);
+    assert_eq!(cfg.check_earley_ready(), Ok(()));
+
+    cfg.rule(a).rhs([x, y, x]).rhs([]);
+    assert_eq!(
+        cfg.check_earley_ready(),
+        Err(EarleyReadyError::NotBinary {
+            rule: 3,
+            lhs: a,
+            rhs: vec![x, y, x],
+        })
+    );
+
+    cfg.remove_rule(3);
+    assert_eq!(
+        cfg.check_earley_ready(),
+        Err(EarleyReadyError::Nulling { rule: 3, lhs: a })
+    );
+}
+
+#[test]
+fn test_rhs_closure_with_witness() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, unproductive, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([unproductive])
+        .rhs([a, b])
+        .rule(a)
+        .rhs([a, unproductive])
+        .rhs([x])
+        .rule(b)
+        .rhs([a, y])
+        .rule(unproductive)
+        .rhs([unproductive, x]);
+
+    let mut productive = SymbolBitSet::terminal_set(&cfg);
+    let witness = cfg.rhs_closure_with_witness(&mut productive, RhsPropertyMode::All);
+
+    assert_eq!(
+        witness.keys().cloned().collect::<Vec<_>>(),
+        vec![start, a, b]
+    );
+    assert!(!productive.has_sym(unproductive));
+    for (&sym, &rule_idx) in &witness {
+        let rule = cfg.rule_at(rule_idx).unwrap();
+        assert_eq!(rule.lhs, sym);
+        assert!(rule.rhs.iter().all(|&rhs_sym| productive.has_sym(rhs_sym)));
+    }
+    assert_eq!(witness[&start], 1);
+    assert_eq!(witness[&a], 3);
+
+    let [empty] = cfg.sym();
+    cfg.rule(empty).rhs([]).rule(unproductive).rhs([empty]);
+    let mut productive = SymbolBitSet::terminal_set(&cfg);
+    let witness = cfg.rhs_closure_with_witness(&mut productive, RhsPropertyMode::All);
+    assert_eq!(witness[&empty], 6);
+    assert_eq!(witness[&unproductive], 7);
+    assert!(productive.has_sym(unproductive));
+}
+
+#[test]
+fn test_clear_histories() {
+    use cfg::history::node::LinkedHistoryNode;
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x] = cfg.sym();
+    cfg.rule(start)
+        .rhs_with_linked_history([a, x], LinkedHistoryNode::Weight { weight: 2.0 })
+        .rule(a)
+        .rhs([x]);
+    cfg.ensure_unique_rule_names();
+
+    let mut plain: Cfg = Cfg::new();
+    let [start, a, x] = plain.sym();
+    plain.rule(start).rhs([a, x]).rule(a).rhs([x]);
+
+    cfg.clear_histories();
+    plain.clear_histories();
+
+    let rules = |cfg: &Cfg| -> Vec<_> {
+        cfg.rules()
+            .map(|rule| (rule.lhs, rule.rhs.to_vec(), rule.history_id))
+            .collect()
+    };
+    assert_eq!(rules(&cfg), rules(&plain));
+    assert_eq!(cfg.history_graph().len(), plain.history_graph().len());
+    assert!(!cfg.has_weights());
+}
+
+#[test]
+fn test_set_weights_from_counts() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+    cfg.rule(start).rhs([a]).rhs([x]).rule(a).rhs([x]).rhs([y]);
+
+    let counts = [(0, 3), (1, 1), (2, 5)].into_iter().collect();
+    cfg.set_weights_from_counts(&counts);
+
+    let weights: Vec<f64> = cfg
+        .rules()
+        .map(|rule| cfg.history_graph().weight(rule.history_id).unwrap())
+        .collect();
+    assert_eq!(&weights[..2], &[0.75, 0.25]);
+    assert!((weights[2] - 5.0 / 5.1).abs() < 1e-9);
+    assert!(weights[3] > 0.0 && weights[3] < 0.05);
+    assert!((weights[2] + weights[3] - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_clear_weights() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([x]).rhs([y]);
+    cfg.set_weights_from_counts(&[(1, 2), (2, 1)].into_iter().collect());
+    cfg.ensure_unique_rule_names();
+    assert!(cfg.has_weights());
+    let before: Vec<_> = cfg
+        .rules()
+        .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+        .collect();
+
+    cfg.clear_weights();
+
+    assert!(!cfg.has_weights());
+    let after: Vec<_> = cfg
+        .rules()
+        .map(|rule| (rule.lhs, rule.rhs.to_vec()))
+        .collect();
+    assert_eq!(before, after);
+    for (i, rule) in cfg.rules().enumerate() {
+        assert_eq!(cfg.history_graph().label(rule.history_id), Some(i as u32));
+    }
+}
+
+#[test]
+fn test_epsilon_only_symbols() {
+    let mut cfg: Cfg = Cfg::new();
+    let [a, b, c, d, x] = cfg.sym();
+    cfg.rule(a)
+        .rhs([])
+        .rhs([b])
+        .rule(b)
+        .rhs([])
+        .rule(c)
+        .rhs([])
+        .rhs([x])
+        .rule(d)
+        .rhs([a, c]);
+
+    let epsilon_only = cfg.epsilon_only_symbols();
+    assert!(epsilon_only.has_sym(a));
+    assert!(epsilon_only.has_sym(b));
+    assert!(!epsilon_only.has_sym(c));
+    assert!(!epsilon_only.has_sym(d));
+    assert!(!epsilon_only.has_sym(x));
+    assert_eq!(epsilon_only.count(), 2);
+}
+
+#[test]
+fn test_flatten_trivial_chains() {
+    use cfg::history::node::LinkedHistoryNode;
+    use cfg::HistoryNode;
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, c, d, x, y] = cfg.sym();
+    cfg.rule(start)
+        .rhs([a, d])
+        .rule(a)
+        .rhs([b])
+        .rule(b)
+        .rhs([c])
+        .rule(c)
+        .rhs([x, y])
+        .rule(d)
+        .rhs([d, x])
+        .rhs([y]);
+    let history_id = cfg.rules().nth(1).unwrap().history_id;
+
+    let mut with_root = cfg.clone();
+    cfg.flatten_trivial_chains(&[start]);
+
+    let mut expected: Cfg = Cfg::new();
+    let [start, a, _b, _c, d, x, y] = expected.sym();
+    expected
+        .rule(start)
+        .rhs([a, d])
+        .rule(a)
+        .rhs([x, y])
+        .rule(d)
+        .rhs([d, x])
+        .rhs([y]);
+    support::assert_eq_rules(expected.rules(), cfg.rules());
+    let new_history_id = cfg.rules().nth(1).unwrap().history_id;
+    match cfg.history_graph()[new_history_id.get()] {
+        HistoryNode::Linked {
+            prev,
+            node: LinkedHistoryNode::Rhs { ref rhs },
+        } => {
+            assert_eq!(prev, history_id);
+            assert_eq!(rhs, &[x, y]);
+        }
+        _ => panic!("expected an Rhs history node"),
+    }
+    assert_eq!(cfg.unused_symbols(), vec![b, c]);
+
+    with_root.flatten_trivial_chains(&[start, b]);
+    assert_eq!(with_root.productions_of(a), vec![vec![x, y]]);
+    assert_eq!(with_root.productions_of(b), vec![vec![x, y]]);
+    assert!(with_root.productions_of(c).is_empty());
+}
+
+#[test]
+fn test_indexed_rules() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([x]).rhs([y, y]);
+
+    let mut count = 0;
+    for (idx, rule) in cfg.indexed_rules() {
+        let at = cfg.rule_at(idx).unwrap();
+        assert_eq!((rule.lhs, rule.rhs), (at.lhs, at.rhs));
+        assert_eq!(rule.history_id, at.history_id);
+        count += 1;
+    }
+    assert_eq!(count, cfg.rule_count());
+}
+
+#[test]
+fn test_factor_subsequence() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, c, a, b, x] = cfg.sym();
+    cfg.rule(start)
+        .rhs([x, a, b, c])
+        .rule(c)
+        .rhs([a, b, a, b, a])
+        .rhs([b, a]);
+
+    let factored = cfg.factor_subsequence(&[a, b]);
+
+    let mut expected: Cfg = Cfg::new();
+    let [start, c, a, b, x, t] = expected.sym();
+    expected
+        .rule(start)
+        .rhs([x, t, c])
+        .rule(c)
+        .rhs([t, t, a])
+        .rhs([b, a])
+        .rule(t)
+        .rhs([a, b]);
+    assert_eq!(factored, t);
+    support::assert_eq_rules(expected.rules(), cfg.rules());
+
+    let [y] = cfg.sym();
+    cfg.rule(y).rhs([x, x, x]);
+    let u = cfg.factor_subsequence(&[x, x]);
+    assert_eq!(cfg.productions_of(y), vec![vec![u, x]]);
+}
+
+#[test]
+fn test_unused_symbols() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, unused, a, x] = cfg.sym();
+
+    cfg.rule(start).rhs([a, x]).rule(a).rhs([]);
+
+    assert_eq!(cfg.unused_symbols(), vec![unused]);
+    let [unused2] = cfg.sym();
+    assert_eq!(cfg.unused_symbols(), vec![unused, unused2]);
+    assert_eq!(SymbolBitSet::used(&cfg).count(), 3);
+}
+
+#[test]
+fn test_terminals_in_appearance_order() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y, z] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, z, b])
+        .rhs([y])
+        .rule(a)
+        .rhs([x, z])
+        .rule(b)
+        .rhs([y, x]);
+
+    assert_eq!(cfg.terminals_in_appearance_order(), vec![z, y, x]);
+}
+
+#[test]
+fn test_content_hash() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+    cfg.rule(start).rhs([a, x]).rhs([y]).rule(a).rhs([x, y]);
+
+    let mut isomorphic: Cfg = Cfg::new();
+    let [_unused, y, a, x, start] = isomorphic.sym();
+    isomorphic
+        .rule(a)
+        .rhs([x, y])
+        .rule(start)
+        .rhs([y])
+        .rhs([a, x]);
+
+    let mut different: Cfg = Cfg::new();
+    let [start, a, x, y] = different.sym();
+    different
+        .rule(start)
+        .rhs([a, x])
+        .rhs([y])
+        .rule(a)
+        .rhs([y, x]);
+
+    assert_eq!(cfg.content_hash(), isomorphic.content_hash());
+    assert_ne!(cfg.content_hash(), different.content_hash());
+}
+
+#[test]
+fn test_from_binarized() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.named_syms(["start", "a", "x", "y"]);
+    cfg.rule(start).rhs([a, x, y]).rule(a).rhs([x]);
+
+    let bin = cfg.binarize();
+    let round_trip = Cfg::from_binarized(&bin);
+
+    assert_eq!(round_trip.num_syms(), bin.num_syms());
+    for sym in [start, a, x, y] {
+        assert_eq!(
+            round_trip.sym_source().name_of(sym),
+            cfg.sym_source().name_of(sym)
+        );
+    }
+    support::assert_eq_rules(bin.rules(), round_trip.rules());
+    assert!(bin
+        .rules()
+        .zip(round_trip.rules())
+        .all(|(a, b)| a.history_id == b.history_id));
+    assert_eq!(round_trip.rule_count(), 3);
+}
+
+#[test]
+fn test_lhs_arity_spread() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x] = cfg.sym();
+    cfg.rule(start)
+        .rhs([a])
+        .rhs([a, x, a, x, a])
+        .rhs([x, x])
+        .rule(a)
+        .rhs([x]);
+
+    let spread = cfg.lhs_arity_spread();
+    assert_eq!(spread[&start], (1, 5));
+    assert_eq!(spread[&a], (1, 1));
+    assert_eq!(spread.get(&x), None);
+}
+
+#[test]
+fn test_rhs_trie() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, lhs, a, b, c, d] = cfg.sym();
+    cfg.rule(start)
+        .rhs([lhs])
+        .rule(lhs)
+        .rhs([a, b])
+        .rhs([a, c])
+        .rhs([d]);
+
+    let trie = cfg.rhs_trie(lhs);
+    assert_eq!(trie.len(), 5);
+    assert_eq!(trie.root().children.len(), 2);
+    assert_eq!(trie.root().rule, None);
+    let shared = trie.find(&[a]).unwrap();
+    assert_eq!(shared.children.len(), 2);
+    assert_eq!(shared.rule, None);
+    assert_eq!(trie.find(&[a, b]).unwrap().rule, Some(1));
+    assert_eq!(trie.find(&[a, c]).unwrap().rule, Some(2));
+    assert_eq!(trie.find(&[d]).unwrap().rule, Some(3));
+    assert_eq!(trie.find(&[b]), None);
+}
+
+#[test]
+fn test_language_equivalent_symbols() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, c, x, y] = cfg.sym();
+    // `a` and `b` both derive `x y` and `x x y`, `c` derives `x y` and `y x`.
+    cfg.rule(start)
+        .rhs([a, b, c])
+        .rule(a)
+        .rhs([x, y])
+        .rhs([x, x, y])
+        .rule(b)
+        .rhs([x, b])
+        .rhs([x, y])
+        .rule(c)
+        .rhs([x, y])
+        .rhs([y, x]);
+
+    assert_eq!(cfg.language_equivalent_symbols(3), vec![(a, b)]);
+    // `b` derives `x x x y` as well.
+    assert_eq!(cfg.language_equivalent_symbols(4), vec![]);
+}
+
+#[test]
+fn test_nullable_derivation_count() {
+    let mut cfg: Cfg = Cfg::new();
+    let [a, b, c] = cfg.sym();
+    cfg.rule(a).rhs([b, c]).rule(b).rhs([]).rule(c).rhs([]);
+
+    assert_eq!(cfg.nullable_derivation_count(a), 1);
+    assert_eq!(cfg.nullable_derivation_count(b), 1);
+
+    let mut cfg: Cfg = Cfg::new();
+    let [a, b, x] = cfg.sym();
+    cfg.rule(a).rhs([]).rhs([b]).rule(b).rhs([]).rhs([x]);
+
+    assert_eq!(cfg.nullable_derivation_count(a), 2);
+    assert_eq!(cfg.nullable_derivation_count(b), 1);
+    assert_eq!(cfg.nullable_derivation_count(x), 0);
+
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a] = cfg.sym();
+    cfg.rule(start).rhs([a]).rule(a).rhs([a]).rhs([]);
+
+    assert_eq!(cfg.nullable_derivation_count(start), usize::MAX);
+}
+
+#[test]
+fn test_enumerate() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, opt, x, y, z] = cfg.sym();
+
+    // start ::= x opt | opt y opt
+    // opt ::= z | ()
+    cfg.rule(start)
+        .rhs([x, opt])
+        .rhs([opt, y, opt])
+        .rule(opt)
+        .rhs([z])
+        .rhs([]);
+
+    let all: Vec<_> = cfg.enumerate(start, 3).collect();
+    assert_eq!(
+        all,
+        vec![
+            vec![x],
+            vec![y],
+            vec![x, z],
+            vec![y, z],
+            vec![z, y],
+            vec![z, y, z],
+        ]
+    );
+    let short: Vec<_> = cfg.enumerate(start, 1).collect();
+    assert_eq!(short, vec![vec![x], vec![y]]);
+    assert_eq!(cfg.enumerate(opt, 0).collect::<Vec<_>>(), vec![vec![]]);
+}
+
+#[test]
+fn test_alternatives_count() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a])
+        .rhs([x, y])
+        .rhs([])
+        .rhs([x, y])
+        .rule(a)
+        .rhs([x]);
+
+    assert_eq!(cfg.alternatives_count(start), 4);
+    assert_eq!(cfg.alternatives_count(a), 1);
+    assert_eq!(cfg.alternatives_count(x), 0);
+    assert_eq!(
+        cfg.alternatives_count(start),
+        cfg.productions_of(start).len()
+    );
+}
+
+#[test]
+fn test_retain_map() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, b, x, y] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, b])
+        .rhs([b])
+        .rule(a)
+        .rhs([x, b])
+        .rule(b)
+        .rhs([y]);
+
+    let revision = cfg.revision();
+    // Replace `b` with `y` and drop the rule for `b`.
+    cfg.retain_map(|rule| {
+        for sym in &mut rule.rhs {
+            if *sym == b {
+                *sym = y;
+            }
+        }
+        rule.lhs != b
+    });
+
+    let mut equivalent: Cfg = Cfg::new();
+    let [start, a, _b, x, y] = equivalent.sym();
+    equivalent
+        .rule(start)
+        .rhs([a, y])
+        .rhs([y])
+        .rule(a)
+        .rhs([x, y]);
+
+    support::assert_eq_rules(equivalent.rules(), cfg.rules());
+    assert!(cfg.revision() > revision);
+}
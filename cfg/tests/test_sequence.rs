@@ -3,7 +3,11 @@
 mod support;
 
 use cfg::classify::useful::Usefulness;
+use cfg::railroad::RailNode;
 use cfg::sequence::Separator::*;
+use cfg::sequence::{
+    normalize_liberal_separators, Separator, SeparatorMode, SequenceDecomposition,
+};
 use cfg::{Cfg, RuleContainer};
 use cfg_sequence::destination::SequenceDestination;
 use cfg_sequence::rewrite::SequencesToProductions;
@@ -95,3 +99,264 @@ fn test_sequence_combinations() {
         assert!(Usefulness::new(&mut cfg).reachable([start]).all_useful());
     }
 }
+
+#[test]
+fn test_separator_from_mode() {
+    let mut cfg: Cfg = Cfg::new();
+    let [sep] = cfg.sym();
+
+    assert_eq!(
+        Separator::from_mode(sep, SeparatorMode::Proper),
+        Proper(sep)
+    );
+    assert_eq!(
+        Separator::from_mode(sep, SeparatorMode::Trailing),
+        Trailing(sep)
+    );
+    assert_eq!(
+        Separator::from_mode(sep, SeparatorMode::Liberal),
+        Liberal(sep)
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid sequence range: start 5 is greater than end 2")]
+fn test_inverted_sequence_range() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, elem] = cfg.sym();
+
+    SequencesToProductions::new(&mut cfg)
+        .sequence(start)
+        .inclusive(5, Some(2))
+        .rhs(elem);
+}
+
+#[test]
+fn test_optional() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x] = cfg.sym();
+
+    let opt = cfg.optional(x);
+    cfg.rule(start).rhs([opt, x]);
+
+    assert_eq!(cfg.productions_of(opt), vec![vec![], vec![x]]);
+    assert_eq!(cfg.shortest_derivations()[&opt], Some(vec![]));
+    assert!(Usefulness::new(&mut cfg).reachable([start]).all_useful());
+}
+
+#[test]
+fn test_auto_label_sequence() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, elem, sep] = cfg.named_syms(["start", "elem", "sep"]);
+
+    SequencesToProductions::new(&mut cfg)
+        .sequence(start)
+        .separator(Trailing(sep))
+        .inclusive(1, Some(1))
+        .rhs(elem);
+    cfg.auto_label();
+
+    let g0 = cfg.productions_of(start)[0][0];
+    assert_eq!(cfg.sym_source().name_of(g0), Some("elem_list"));
+}
+
+#[test]
+fn test_railroad_model() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, list, elem, sep, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([list]).rhs([x, y]);
+    SequencesToProductions::new(&mut cfg)
+        .sequence(list)
+        .separator(Trailing(sep))
+        .inclusive(2, None)
+        .rhs(elem);
+
+    let model = cfg.railroad_model();
+    assert_eq!(
+        model[&start],
+        RailNode::Choice(vec![
+            RailNode::Sequence(vec![RailNode::Nonterminal(list)]),
+            RailNode::Sequence(vec![RailNode::Terminal(x), RailNode::Terminal(y)]),
+        ])
+    );
+    assert_eq!(
+        model[&list],
+        RailNode::Repeat {
+            item: Box::new(RailNode::Terminal(elem)),
+            separator: Some(Box::new(RailNode::Terminal(sep))),
+            min: 2,
+            max: None,
+        }
+    );
+    assert_eq!(model.len(), 2);
+}
+
+#[test]
+fn test_railroad_model_mixed_alternatives() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, x, y] = cfg.sym();
+
+    cfg.rule(start).rhs([y]);
+    SequencesToProductions::new(&mut cfg)
+        .sequence(start)
+        .inclusive(0, None)
+        .rhs(x)
+        .sequence(start)
+        .inclusive(2, Some(5))
+        .rhs(y);
+
+    let repeat = |item, min, max| RailNode::Repeat {
+        item: Box::new(RailNode::Terminal(item)),
+        separator: None,
+        min,
+        max,
+    };
+    let model = cfg.railroad_model();
+    assert_eq!(
+        model[&start],
+        RailNode::Choice(vec![
+            RailNode::Sequence(vec![RailNode::Terminal(y)]),
+            repeat(x, 0, None),
+            repeat(y, 2, Some(5)),
+        ])
+    );
+    assert_eq!(model.len(), 1);
+}
+
+#[test]
+fn test_sequence_decomposition() {
+    let strategies = [
+        SequenceDecomposition::PowerOfTwo,
+        SequenceDecomposition::Linear,
+        SequenceDecomposition::Balanced,
+    ];
+    let mut first_rhs = vec![];
+    for decomposition in strategies {
+        let mut cfg: Cfg = Cfg::new();
+        let [start, x] = cfg.sym();
+
+        SequencesToProductions::new(&mut cfg)
+            .decomposition(decomposition)
+            .sequence(start)
+            .inclusive(3, Some(3))
+            .rhs(x);
+
+        let all: Vec<_> = cfg.enumerate(start, 6).collect();
+        assert_eq!(all, vec![vec![x, x, x]]);
+        let rhs = cfg.productions_of(start).remove(0);
+        first_rhs.push(
+            rhs.iter()
+                .map(|&sym| cfg.productions_of(sym))
+                .collect::<Vec<_>>(),
+        );
+    }
+    // `x x` followed by `x`, then `x` followed by `x x`.
+    assert_ne!(first_rhs[0], first_rhs[1]);
+    assert_eq!(first_rhs[1], first_rhs[2]);
+
+    let mut rule_counts = vec![];
+    for decomposition in strategies {
+        let mut cfg: Cfg = Cfg::new();
+        let [start, x] = cfg.sym();
+
+        SequencesToProductions::new(&mut cfg)
+            .decomposition(decomposition)
+            .sequence(start)
+            .inclusive(5, Some(5))
+            .rhs(x);
+
+        let all: Vec<_> = cfg.enumerate(start, 10).collect();
+        assert_eq!(all, vec![vec![x; 5]]);
+        rule_counts.push(cfg.rule_count());
+    }
+    assert_eq!(rule_counts, vec![4, 5, 4]);
+}
+
+#[test]
+fn test_normalize_liberal_separators() {
+    let mut liberal: Cfg = Cfg::new();
+    let [start, elem, sep, other] = liberal.sym();
+
+    let mut sequences = vec![];
+    (&mut sequences)
+        .sequence(start)
+        .separator(Liberal(sep))
+        .inclusive(1, Some(3))
+        .rhs(elem)
+        .sequence(other)
+        .inclusive(0, None)
+        .rhs(elem);
+    let mut normalized = sequences.clone();
+    normalize_liberal_separators(&mut normalized);
+
+    assert_eq!(normalized.len(), 3);
+    assert_eq!(normalized[0].separator, Proper(sep));
+    assert_eq!(normalized[1].separator, Trailing(sep));
+    assert_eq!(normalized[2], sequences[1]);
+
+    let mut proper_trailing = liberal.clone();
+    SequencesToProductions::rewrite_sequences(&sequences, &mut liberal);
+    SequencesToProductions::rewrite_sequences(&normalized, &mut proper_trailing);
+    assert_eq!(
+        liberal.enumerate(start, 7).collect::<Vec<_>>(),
+        proper_trailing.enumerate(start, 7).collect::<Vec<_>>()
+    );
+
+    let mut liberal: Cfg = Cfg::new();
+    let [start, elem, sep] = liberal.sym();
+    let mut sequences = vec![];
+    (&mut sequences)
+        .sequence(start)
+        .separator(Liberal(sep))
+        .inclusive(0, None)
+        .rhs(elem);
+    let mut normalized = sequences.clone();
+    normalize_liberal_separators(&mut normalized);
+
+    assert_eq!(normalized.len(), 2);
+    assert_eq!((normalized[0].start, normalized[0].end), (0, None));
+    assert_eq!((normalized[1].start, normalized[1].end), (1, None));
+
+    let mut proper_trailing = liberal.clone();
+    SequencesToProductions::rewrite_sequences(&sequences, &mut liberal);
+    SequencesToProductions::rewrite_sequences(&normalized, &mut proper_trailing);
+    let nulling_rules = proper_trailing
+        .rules()
+        .filter(|rule| rule.lhs == start && rule.rhs.is_empty())
+        .count();
+    assert_eq!(nulling_rules, 1);
+    assert_eq!(
+        liberal.enumerate(start, 7).collect::<Vec<_>>(),
+        proper_trailing.enumerate(start, 7).collect::<Vec<_>>()
+    );
+    let binarized = proper_trailing.binarize();
+    assert!(binarized.rules().any(|rule| rule.lhs == start));
+}
+
+#[test]
+fn test_debug_history() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, elem, sep] = cfg.named_syms(["start", "elem", "sep"]);
+
+    SequencesToProductions::new(&mut cfg)
+        .sequence(start)
+        .separator(Proper(sep))
+        .inclusive(3, Some(3))
+        .rhs(elem);
+    let bin = cfg.binarize();
+
+    let dumps: Vec<String> = (0..bin.rules().count())
+        .map(|idx| bin.debug_history(idx))
+        .collect();
+    let dump = dumps
+        .iter()
+        .find(|dump| dump.contains("rewrite sequence") && dump.contains("binarize"))
+        .expect("no rule with both transformations");
+    assert!(dump.contains("rewrite sequence top of elem"), "{}", dump);
+    assert!(
+        dump.lines().last().unwrap().starts_with("binarize"),
+        "{}",
+        dump
+    );
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "serialize")]
+
+use cfg::history::node::{LinkedHistoryNode, RootHistoryNode};
+use cfg::{Cfg, HistoryNode, RuleContainer};
+
+#[test]
+fn test_json_round_trip() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a] = cfg.named_syms(["start", "a"]);
+    let [x, y] = cfg.sym();
+    let origin = cfg.add_history_node(RootHistoryNode::Origin { origin: 7 }.into());
+    let weight = cfg.add_history_node(HistoryNode::Linked {
+        prev: origin,
+        node: LinkedHistoryNode::Weight { weight: 0.5 },
+    });
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .history(weight)
+        .rhs([y])
+        .rule(a)
+        .rhs([])
+        .rhs([x, y]);
+    let sequence = cfg.add_history_node(HistoryNode::Linked {
+        prev: origin,
+        node: LinkedHistoryNode::RewriteSequence {
+            top: true,
+            rhs: x,
+            sep: None,
+            start: 2,
+            end: Some(5),
+        },
+    });
+    cfg.rule(start).history(sequence).rhs([x, x]);
+    cfg.ensure_unique_rule_names();
+
+    let json = cfg.to_json();
+    let deserialized = Cfg::from_json(&json).unwrap();
+
+    let rules = |cfg: &Cfg| -> Vec<_> {
+        cfg.rules()
+            .map(|rule| (rule.lhs, rule.rhs.to_vec(), rule.history_id))
+            .collect()
+    };
+    assert_eq!(rules(&deserialized), rules(&cfg));
+    assert_eq!(deserialized.num_syms(), cfg.num_syms());
+    let names = |cfg: &Cfg| -> Vec<_> {
+        [start, a, x, y]
+            .iter()
+            .map(|&sym| cfg.sym_source().name_of(sym).map(String::from))
+            .collect()
+    };
+    assert_eq!(names(&deserialized), names(&cfg));
+    assert_eq!(deserialized.sym_source().name_of(start), Some("start"));
+    assert_eq!(
+        deserialized.history_graph().len(),
+        cfg.history_graph().len()
+    );
+    assert_eq!(deserialized.to_json(), json);
+    assert_eq!(
+        deserialized.history_graph().sequence_bounds(sequence),
+        Some((2, Some(5)))
+    );
+}
+
+#[test]
+fn test_json_invalid() {
+    assert!(Cfg::from_json("{}").is_err());
+    assert!(Cfg::from_json(
+        r#"{"num_syms":1,"rules":[{"lhs":0,"rhs":[],"history_id":5}],"history":[]}"#
+    )
+    .is_err());
+    assert!(
+        Cfg::from_json(r#"{"num_syms":1,"names":[null,"x"],"rules":[],"history":[]}"#).is_err()
+    );
+    // Rule symbols must be below `num_syms`.
+    let rule = |lhs: u64, rhs: u64| {
+        format!(
+            r#"{{"num_syms":2,"names":[],"rules":[{{"lhs":{},"rhs":[0,{}],"history_id":1}}],"history":[{{"kind":"NoOp","prev":null,"syms":[],"values":[],"weight":null}},{{"kind":"Rule","prev":null,"syms":[0],"values":[],"weight":null}}]}}"#,
+            lhs, rhs
+        )
+    };
+    assert!(Cfg::from_json(&rule(0, 1)).is_ok());
+    assert!(Cfg::from_json(&rule(0, 50)).is_err());
+    assert!(Cfg::from_json(&rule(2, 1)).is_err());
+    // A merged node must refer to an earlier node.
+    let merged = |other: u64| {
+        format!(
+            r#"{{"num_syms":1,"names":[],"rules":[],"history":[{{"kind":"NoOp","prev":null,"syms":[],"values":[],"weight":null}},{{"kind":"Rule","prev":null,"syms":[0],"values":[],"weight":null}},{{"kind":"Merged","prev":1,"syms":[],"values":[{}],"weight":null}}]}}"#,
+            other
+        )
+    };
+    assert!(Cfg::from_json(&merged(1)).is_ok());
+    assert!(Cfg::from_json(&merged(2)).is_err());
+    assert!(Cfg::from_json(&merged(7)).is_err());
+}
@@ -0,0 +1,45 @@
+#![cfg(feature = "cfg-classify")]
+
+use cfg::classify::useful::Usefulness;
+use cfg::{Cfg, RuleContainer};
+
+#[test]
+fn test_remove_useless_rules_with_diagnostics() {
+    let mut cfg: Cfg = Cfg::new();
+    let [start, a, unreachable, unproductive, x, y, z] = cfg.sym();
+
+    cfg.rule(start)
+        .rhs([a, x])
+        .rhs([unproductive, y])
+        .rule(a)
+        .rhs([y])
+        .rule(unreachable)
+        .rhs([z])
+        .rule(unproductive)
+        .rhs([unproductive, x]);
+
+    let removed = Usefulness::new(&mut cfg)
+        .reachable([start])
+        .remove_useless_rules_with_diagnostics();
+
+    let removed: Vec<_> = removed
+        .iter()
+        .map(|(rule, usefulness)| {
+            (
+                rule.lhs,
+                rule.rhs.clone(),
+                usefulness.is_reachable(),
+                usefulness.is_productive(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        removed,
+        vec![
+            (start, vec![unproductive, y], true, false),
+            (unreachable, vec![z], false, true),
+            (unproductive, vec![unproductive, x], true, false),
+        ]
+    );
+    assert_eq!(cfg.rules().count(), 2);
+}